@@ -0,0 +1,172 @@
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::utils::{address::Address, bytes32::Bytes32, crypto::keccak256};
+
+pub const DEFAULT_ADDRESS: u64 = 0x1000;
+pub const DEFAULT_CALLER: u64 = 0x2000;
+
+/// BLOCKHASH only sees the most recent 256 complete blocks.
+pub const BLOCK_HASH_HISTORY: u64 = 256;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BlockEnv {
+    pub coinbase: Address,
+    pub timestamp: u64,
+    pub number: u64,
+    pub prevrandao: Bytes32,
+    pub gas_limit: u64,
+    pub chain_id: u64,
+    pub basefee: u128,
+    pub block_hashes: HashMap<u64, Bytes32>,
+}
+
+impl Default for BlockEnv {
+    fn default() -> Self {
+        Self {
+            coinbase: Address::zero(),
+            timestamp: 1,
+            number: 1,
+            prevrandao: Bytes32::from(0),
+            gas_limit: 30_000_000,
+            chain_id: 1,
+            basefee: 0,
+            block_hashes: HashMap::new(),
+        }
+    }
+}
+
+impl BlockEnv {
+    /// Returns the hash of the given block, or zero when it is outside of the
+    /// 256 block window. Hashes missing from `block_hashes` are derived as the
+    /// keccak256 of the block number.
+    pub fn block_hash(&self, number: u64) -> Bytes32 {
+        if number >= self.number || self.number - number > BLOCK_HASH_HISTORY {
+            return Bytes32::from(0);
+        }
+
+        match self.block_hashes.get(&number) {
+            Some(hash) => *hash,
+            None => keccak256(&Bytes32::from(number as u128).0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TxEnv {
+    pub address: Address,
+    pub origin: Address,
+    pub caller: Address,
+    pub value: u128,
+    pub gas_price: u128,
+}
+
+impl Default for TxEnv {
+    fn default() -> Self {
+        Self {
+            address: Address::from(DEFAULT_ADDRESS),
+            origin: Address::from(DEFAULT_CALLER),
+            caller: Address::from(DEFAULT_CALLER),
+            value: 0,
+            gas_price: 0,
+        }
+    }
+}
+
+/// Execution environment as read from an env JSON file.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Env {
+    pub block: BlockEnv,
+    pub tx: TxEnv,
+}
+
+impl Env {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_creates_block_env_with_defaults() {
+        let block = BlockEnv::default();
+
+        assert_eq!(block.number, 1);
+        assert_eq!(block.chain_id, 1);
+        assert_eq!(block.gas_limit, 30_000_000);
+    }
+
+    #[test]
+    fn it_returns_block_hash_within_window() -> Result<(), Box<dyn Error>> {
+        let mut block = BlockEnv {
+            number: 1000,
+            ..Default::default()
+        };
+        block
+            .block_hashes
+            .insert(999, "aa".repeat(32).parse::<Bytes32>()?);
+
+        assert_eq!(block.block_hash(999).to_string(), "aa".repeat(32));
+        assert_eq!(block.block_hash(744), keccak256(&Bytes32::from(744u128).0));
+        assert_eq!(block.block_hash(743), Bytes32::from(0));
+        assert_eq!(block.block_hash(1000), Bytes32::from(0));
+        assert_eq!(block.block_hash(1001), Bytes32::from(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_env_with_partial_block() -> Result<(), Box<dyn Error>> {
+        let env: Env = serde_json::from_str(
+            r#"{
+                "block": {
+                    "number": 19000000,
+                    "timestamp": 1700000000,
+                    "coinbase": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+                    "block_hashes": {
+                        "18999999": "0x0102"
+                    }
+                }
+            }"#,
+        )?;
+
+        assert_eq!(env.block.number, 19_000_000);
+        assert_eq!(env.block.timestamp, 1_700_000_000);
+        assert_eq!(
+            env.block.coinbase,
+            "95222290dd7278aa3ddd389cc1e1d165cc4bafe5".parse::<Address>()?
+        );
+        assert_eq!(env.block.chain_id, 1);
+        assert_eq!(env.block.block_hash(18_999_999), Bytes32::from(0x0102));
+        assert_eq!(env.tx, TxEnv::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_env_with_tx() -> Result<(), Box<dyn Error>> {
+        let env: Env = serde_json::from_str(
+            r#"{
+                "tx": {
+                    "caller": "0x00000000000000000000000000000000000000aa",
+                    "value": 1000000000000000000
+                }
+            }"#,
+        )?;
+
+        assert_eq!(env.tx.caller, Address::from(0xaa));
+        assert_eq!(env.tx.origin, Address::from(DEFAULT_CALLER));
+        assert_eq!(env.tx.value, 1_000_000_000_000_000_000);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,455 @@
+use std::str::FromStr;
+
+use crate::{instruction::InstructionType, utils::errors::EofError};
+
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+pub const EOF_VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_DATA: u8 = 0x04;
+const TERMINATOR: u8 = 0x00;
+
+const TYPE_SIZE: usize = 4;
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_IO_ITEMS: u8 = 0x7f;
+const MAX_STACK_HEIGHT: u16 = 1023;
+
+/// Inputs, outputs and the maximum stack height of a code section (EIP-4750).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypeSection {
+    pub inputs: u8,
+    pub outputs: u8,
+    pub max_stack_height: u16,
+}
+
+/// A decoded EOF v1 container (EIP-3540). Code sections are kept as offsets into
+/// the container so that the legacy lexer can execute them in place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EofContainer {
+    pub types: Vec<TypeSection>,
+    pub code_offsets: Vec<usize>,
+    pub code_sections: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+}
+
+/// Returns true if the code is prefixed with the EOF magic.
+pub fn is_eof(code: &[u8]) -> bool {
+    code.starts_with(&EOF_MAGIC)
+}
+
+impl EofContainer {
+    /// Decodes the header and sections of the container without validating the code.
+    pub fn decode(container: &[u8]) -> Result<Self, EofError> {
+        if !is_eof(container) {
+            return Err(EofError::InvalidMagic);
+        }
+
+        if container.get(2) != Some(&EOF_VERSION) {
+            return Err(EofError::InvalidVersion);
+        }
+
+        let mut reader = Reader {
+            bytes: container,
+            position: 3,
+        };
+
+        reader.expect(KIND_TYPES)?;
+        let types_size = reader.read_u16()? as usize;
+
+        reader.expect(KIND_CODE)?;
+        let sections = reader.read_u16()? as usize;
+
+        if sections == 0 || sections > MAX_CODE_SECTIONS || types_size != sections * TYPE_SIZE {
+            return Err(EofError::InvalidHeader);
+        }
+
+        let mut code_sizes = Vec::with_capacity(sections);
+        for _ in 0..sections {
+            let size = reader.read_u16()? as usize;
+
+            if size == 0 {
+                return Err(EofError::InvalidHeader);
+            }
+
+            code_sizes.push(size);
+        }
+
+        reader.expect(KIND_DATA)?;
+        let data_size = reader.read_u16()? as usize;
+
+        reader.expect(TERMINATOR)?;
+
+        let body_size = types_size + code_sizes.iter().sum::<usize>() + data_size;
+        if container.len() != reader.position + body_size {
+            return Err(EofError::InvalidContainerSize);
+        }
+
+        let types = reader
+            .read_bytes(types_size)?
+            .chunks(TYPE_SIZE)
+            .map(|chunk| TypeSection {
+                inputs: chunk[0],
+                outputs: chunk[1],
+                max_stack_height: u16::from_be_bytes([chunk[2], chunk[3]]),
+            })
+            .collect();
+
+        let mut code_offsets = Vec::with_capacity(sections);
+        let mut code_sections = Vec::with_capacity(sections);
+        for size in code_sizes {
+            code_offsets.push(reader.position);
+            code_sections.push(reader.read_bytes(size)?.to_vec());
+        }
+
+        let data = reader.read_bytes(data_size)?.to_vec();
+
+        Ok(Self {
+            types,
+            code_offsets,
+            code_sections,
+            data,
+        })
+    }
+
+    /// Validates the type section and every code section (EIP-3670, EIP-4200,
+    /// EIP-4750 and EIP-5450).
+    pub fn validate(&self) -> Result<(), EofError> {
+        let first = self.types[0];
+        if first.inputs != 0 || first.outputs != 0 {
+            return Err(EofError::InvalidTypeSection);
+        }
+
+        for section in &self.types {
+            if section.inputs > MAX_IO_ITEMS
+                || section.outputs > MAX_IO_ITEMS
+                || section.max_stack_height > MAX_STACK_HEIGHT
+            {
+                return Err(EofError::InvalidTypeSection);
+            }
+        }
+
+        for index in 0..self.code_sections.len() {
+            self.validate_code(index)?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes and validates the container.
+    pub fn parse(container: &[u8]) -> Result<Self, EofError> {
+        let container = Self::decode(container)?;
+        container.validate()?;
+
+        Ok(container)
+    }
+
+    fn validate_code(&self, index: usize) -> Result<(), EofError> {
+        let instructions = self.decode_instructions(index)?;
+        let code = &self.code_sections[index];
+        let section = self.types[index];
+
+        // NOTE: instructions are visited in order, so a backward jump always targets
+        // an instruction whose stack height is already known
+        let mut heights: Vec<Option<u16>> = vec![None; code.len()];
+        let mut is_instruction = vec![false; code.len()];
+        for (offset, _) in &instructions {
+            is_instruction[*offset] = true;
+        }
+
+        heights[0] = Some(section.inputs as u16);
+        let mut max_height = section.inputs as u16;
+
+        for (offset, instruction) in &instructions {
+            let height = heights[*offset].ok_or(EofError::UnreachableCode)?;
+            let next = offset + 1 + instruction.immediate_size();
+
+            let (inputs, outputs) = match instruction {
+                InstructionType::CALLF => {
+                    let target = self.types[read_u16(code, offset + 1) as usize];
+                    (target.inputs as u16, target.outputs as u16)
+                }
+                InstructionType::RETF => {
+                    if index == 0 || height != section.outputs as u16 {
+                        return Err(EofError::InvalidStackHeight);
+                    }
+                    (0, 0)
+                }
+                _ => instruction.stack_io(),
+            };
+
+            if height < inputs {
+                return Err(EofError::InvalidStackHeight);
+            }
+
+            let height = height - inputs + outputs;
+            max_height = max_height.max(height);
+
+            let mut successors = vec![];
+
+            if matches!(
+                instruction,
+                InstructionType::RJUMP | InstructionType::RJUMPI
+            ) {
+                let relative = read_u16(code, offset + 1) as i16;
+                let target = usize::try_from(next as isize + relative as isize)
+                    .map_err(|_| EofError::InvalidRelativeJump)?;
+
+                if !is_instruction.get(target).copied().unwrap_or(false) {
+                    return Err(EofError::InvalidRelativeJump);
+                }
+
+                successors.push(target);
+            }
+
+            if !instruction.is_terminating() && !matches!(instruction, InstructionType::RJUMP) {
+                if next >= code.len() {
+                    return Err(EofError::NoTerminatingInstruction);
+                }
+
+                successors.push(next);
+            }
+
+            for successor in successors {
+                match heights[successor] {
+                    Some(expected) if expected != height => {
+                        return Err(EofError::InvalidStackHeight);
+                    }
+                    Some(_) => {}
+                    None => heights[successor] = Some(height),
+                }
+            }
+        }
+
+        if max_height != section.max_stack_height {
+            return Err(EofError::MaxStackHeightMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Splits a code section into instructions and checks that every opcode is allowed
+    /// and every immediate fits into the section.
+    fn decode_instructions(&self, index: usize) -> Result<Vec<(usize, InstructionType)>, EofError> {
+        let code = &self.code_sections[index];
+        let mut instructions = vec![];
+        let mut offset = 0;
+
+        while offset < code.len() {
+            let opcode = code[offset];
+            let instruction = InstructionType::from_str(&format!("{:02x}", opcode))
+                .map_err(|_| EofError::InvalidInstruction(opcode))?;
+
+            if matches!(
+                instruction,
+                InstructionType::CALLCODE | InstructionType::SELFDESTRUCT
+            ) {
+                return Err(EofError::InvalidInstruction(opcode));
+            }
+
+            let next = offset + 1 + instruction.immediate_size();
+            if next > code.len() {
+                return Err(EofError::TruncatedImmediate);
+            }
+
+            if matches!(instruction, InstructionType::CALLF)
+                && read_u16(code, offset + 1) as usize >= self.code_sections.len()
+            {
+                return Err(EofError::InvalidCodeSectionIndex);
+            }
+
+            instructions.push((offset, instruction));
+            offset = next;
+        }
+
+        Ok(instructions)
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, size: usize) -> Result<&'a [u8], EofError> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + size)
+            .ok_or(EofError::InvalidHeader)?;
+        self.position += size;
+
+        Ok(bytes)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, EofError> {
+        let bytes = self.read_bytes(2)?;
+
+        Ok(read_u16(bytes, 0))
+    }
+
+    fn expect(&mut self, kind: u8) -> Result<(), EofError> {
+        if self.read_bytes(1)?[0] != kind {
+            return Err(EofError::InvalidHeader);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // NOTE: helper function, builds a container from (inputs, outputs, max stack height, code)
+    fn build_container(sections: &[(u8, u8, u16, &str)], data: &str) -> Vec<u8> {
+        let mut header = format!("ef000101{:04x}02{:04x}", 4 * sections.len(), sections.len());
+        for (_, _, _, code) in sections {
+            header += &format!("{:04x}", code.len() / 2);
+        }
+        header += &format!("04{:04x}00", data.len() / 2);
+
+        for (inputs, outputs, max_stack_height, _) in sections {
+            header += &format!("{inputs:02x}{outputs:02x}{max_stack_height:04x}");
+        }
+        for (_, _, _, code) in sections {
+            header += code;
+        }
+        header += data;
+
+        hex::decode(header).unwrap()
+    }
+
+    #[test]
+    fn it_decodes_container() -> Result<(), EofError> {
+        let bytes = build_container(&[(0, 0, 1, "600100"), (1, 1, 1, "e4")], "aabb");
+        let container = EofContainer::parse(&bytes)?;
+
+        assert_eq!(container.types.len(), 2);
+        assert_eq!(
+            container.types[1],
+            TypeSection {
+                inputs: 1,
+                outputs: 1,
+                max_stack_height: 1
+            }
+        );
+        assert_eq!(container.code_sections[0], vec![0x60, 0x01, 0x00]);
+        assert_eq!(container.code_offsets, vec![25, 28]);
+        assert_eq!(container.data, vec![0xaa, 0xbb]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_header_returns_eof_error() {
+        let bytes = build_container(&[(0, 0, 0, "00")], "");
+
+        assert_eq!(
+            EofContainer::decode(&bytes[..bytes.len() - 1]),
+            Err(EofError::InvalidContainerSize)
+        );
+        assert_eq!(
+            EofContainer::decode(&[0xef, 0x00, 0x02]),
+            Err(EofError::InvalidVersion)
+        );
+        assert_eq!(
+            EofContainer::decode(&[0xef, 0x00, 0x01, 0x02]),
+            Err(EofError::InvalidHeader)
+        );
+        assert_eq!(EofContainer::decode(&[0x60]), Err(EofError::InvalidMagic));
+    }
+
+    #[test]
+    fn it_validates_relative_jumps() {
+        // NOTE: PUSH1 1, RJUMPI +1, STOP, STOP
+        let bytes = build_container(&[(0, 0, 1, "6001e100010000")], "");
+        assert_eq!(EofContainer::parse(&bytes).map(|_| ()), Ok(()));
+
+        // NOTE: the jump lands in the immediate of PUSH1
+        let bytes = build_container(&[(0, 0, 1, "e0000160010000")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::InvalidRelativeJump)
+        );
+
+        // NOTE: a backward jump to itself is an infinite loop, which is valid
+        let bytes = build_container(&[(0, 0, 0, "e0fffd")], "");
+        assert_eq!(EofContainer::parse(&bytes).map(|_| ()), Ok(()));
+    }
+
+    #[test]
+    fn it_validates_code() {
+        let bytes = build_container(&[(0, 0, 0, "0c00")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::InvalidInstruction(0x0c))
+        );
+
+        let bytes = build_container(&[(0, 0, 1, "6000ff")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::InvalidInstruction(0xff))
+        );
+
+        let bytes = build_container(&[(0, 0, 0, "61aa")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::TruncatedImmediate)
+        );
+
+        let bytes = build_container(&[(0, 0, 1, "6001")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::NoTerminatingInstruction)
+        );
+
+        let bytes = build_container(&[(0, 0, 0, "000000")], "");
+        assert_eq!(EofContainer::parse(&bytes), Err(EofError::UnreachableCode));
+
+        let bytes = build_container(&[(0, 0, 0, "e3000100")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::InvalidCodeSectionIndex)
+        );
+    }
+
+    #[test]
+    fn it_validates_stack_heights() {
+        let bytes = build_container(&[(0, 0, 0, "5000")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::InvalidStackHeight)
+        );
+
+        let bytes = build_container(&[(0, 0, 2, "6001600100")], "");
+        assert_eq!(EofContainer::parse(&bytes).map(|_| ()), Ok(()));
+
+        let bytes = build_container(&[(0, 0, 1, "6001600100")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::MaxStackHeightMismatch)
+        );
+
+        // NOTE: CALLF consumes one item and returns two, RETF needs the declared outputs
+        let bytes = build_container(&[(0, 0, 2, "6001e3000100"), (1, 2, 2, "8080e4")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::InvalidStackHeight)
+        );
+
+        let bytes = build_container(&[(0, 0, 2, "6001e3000100"), (1, 2, 2, "80e4")], "");
+        assert_eq!(EofContainer::parse(&bytes).map(|_| ()), Ok(()));
+
+        // NOTE: the first section must not take inputs
+        let bytes = build_container(&[(1, 0, 1, "00")], "");
+        assert_eq!(
+            EofContainer::parse(&bytes),
+            Err(EofError::InvalidTypeSection)
+        );
+    }
+}
@@ -0,0 +1,558 @@
+use crate::{
+    instruction::InstructionType,
+    spec::SpecId,
+    utils::{bytes32::Bytes32, errors::GasError},
+};
+
+pub const DEFAULT_GAS_LIMIT: u64 = 30_000_000;
+
+pub const WORD_SIZE: u64 = 32;
+pub const MEMORY_WORD_GAS: u64 = 3;
+pub const QUADRATIC_DENOMINATOR: u64 = 512;
+pub const KECCAK256_WORD_GAS: u64 = 6;
+pub const COPY_WORD_GAS: u64 = 3;
+pub const EXP_BYTE_GAS: u64 = 50;
+pub const EXP_BYTE_GAS_FRONTIER: u64 = 10;
+pub const LOG_GAS: u64 = 375;
+pub const LOG_TOPIC_GAS: u64 = 375;
+pub const LOG_DATA_GAS: u64 = 8;
+
+pub const WARM_ACCOUNT_ACCESS_GAS: u64 = 100;
+pub const COLD_ACCOUNT_ACCESS_GAS: u64 = 2_600;
+pub const CALL_VALUE_GAS: u64 = 9_000;
+pub const CALL_STIPEND: u64 = 2_300;
+pub const NEW_ACCOUNT_GAS: u64 = 25_000;
+pub const CREATE_GAS: u64 = 32_000;
+pub const INITCODE_WORD_GAS: u64 = 2;
+pub const CODE_DEPOSIT_GAS: u64 = 200;
+
+pub const TX_BASE_GAS: u64 = 21_000;
+pub const TX_CREATE_GAS: u64 = 32_000;
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+pub const TX_DATA_NON_ZERO_GAS: u64 = 16;
+pub const TX_DATA_NON_ZERO_GAS_FRONTIER: u64 = 68;
+
+pub const SELFDESTRUCT_GAS: u64 = 5_000;
+pub const SELFDESTRUCT_REFUND: i64 = 24_000;
+
+pub const SSTORE_SET_GAS: u64 = 20_000;
+pub const SSTORE_RESET_GAS: u64 = 2_900;
+pub const SSTORE_RESET_GAS_FRONTIER: u64 = 5_000;
+pub const SLOAD_GAS_ISTANBUL: u64 = 800;
+pub const WARM_STORAGE_READ_GAS: u64 = 100;
+pub const COLD_SLOAD_GAS: u64 = 2_100;
+pub const SSTORE_SENTRY_GAS: u64 = 2_300;
+pub const SSTORE_CLEARS_REFUND: i64 = 4_800;
+pub const SSTORE_CLEARS_REFUND_FRONTIER: i64 = 15_000;
+
+pub const ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+pub const ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Gas {
+    limit: u64,
+    used: u64,
+    refunded: i64,
+}
+
+impl Default for Gas {
+    fn default() -> Self {
+        Self::new(DEFAULT_GAS_LIMIT)
+    }
+}
+
+impl Gas {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            used: 0,
+            refunded: 0,
+        }
+    }
+
+    pub fn charge(&mut self, amount: u64) -> Result<(), GasError> {
+        if amount > self.remaining() {
+            self.used = self.limit;
+            return Err(GasError::OutOfGas);
+        }
+
+        self.used += amount;
+
+        Ok(())
+    }
+
+    /// Adds to the refund counter, a negative amount takes back an earlier refund (EIP-2200).
+    pub fn refund(&mut self, amount: i64) {
+        self.refunded += amount;
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.limit - self.used
+    }
+
+    pub fn refunded(&self) -> i64 {
+        self.refunded
+    }
+}
+
+pub fn static_cost(instruction: &InstructionType, spec: SpecId) -> u64 {
+    match instruction {
+        InstructionType::STOP => 0,
+        InstructionType::ADD | InstructionType::SUB => 3,
+        InstructionType::MUL | InstructionType::DIV | InstructionType::MOD => 5,
+        InstructionType::EXP => 10,
+        InstructionType::LT
+        | InstructionType::GT
+        | InstructionType::EQ
+        | InstructionType::ISZERO
+        | InstructionType::AND
+        | InstructionType::OR
+        | InstructionType::XOR
+        | InstructionType::NOT
+        | InstructionType::BYTE => 3,
+        InstructionType::KECCAK256 => 30,
+        InstructionType::CALLDATALOAD | InstructionType::CALLDATACOPY => 3,
+        InstructionType::CODECOPY | InstructionType::RETURNDATACOPY => 3,
+        InstructionType::BLOCKHASH => 20,
+        InstructionType::SELFBALANCE => 5,
+        InstructionType::POP
+        | InstructionType::GAS
+        | InstructionType::CALLDATASIZE
+        | InstructionType::CODESIZE
+        | InstructionType::RETURNDATASIZE
+        | InstructionType::ADDRESS
+        | InstructionType::ORIGIN
+        | InstructionType::CALLER
+        | InstructionType::CALLVALUE
+        | InstructionType::GASPRICE
+        | InstructionType::COINBASE
+        | InstructionType::TIMESTAMP
+        | InstructionType::NUMBER
+        | InstructionType::PREVRANDAO
+        | InstructionType::GASLIMIT
+        | InstructionType::CHAINID
+        | InstructionType::BASEFEE => 2,
+        InstructionType::MLOAD | InstructionType::MSTORE => 3,
+        // NOTE: since Berlin the cold access surcharge is added by `cold_sload_cost`
+        InstructionType::SLOAD if spec.is_enabled_in(SpecId::Berlin) => WARM_STORAGE_READ_GAS,
+        InstructionType::SLOAD if spec.is_enabled_in(SpecId::Istanbul) => SLOAD_GAS_ISTANBUL,
+        InstructionType::SLOAD if spec.is_enabled_in(SpecId::TangerineWhistle) => 200,
+        InstructionType::SLOAD => 50,
+        // NOTE: SSTORE is fully priced by `sstore_cost`
+        InstructionType::SSTORE => 0,
+        InstructionType::PUSH(0) => 2,
+        InstructionType::PUSH(_) | InstructionType::DUP(_) | InstructionType::SWAP(_) => 3,
+        InstructionType::LOG(topics) => LOG_GAS + LOG_TOPIC_GAS * *topics as u64,
+        // NOTE: since Berlin the cold access surcharge is added by `cold_account_access_cost`
+        InstructionType::BALANCE
+        | InstructionType::EXTCODESIZE
+        | InstructionType::EXTCODECOPY
+        | InstructionType::EXTCODEHASH
+        | InstructionType::CALL
+        | InstructionType::CALLCODE
+        | InstructionType::DELEGATECALL
+        | InstructionType::STATICCALL
+            if spec.is_enabled_in(SpecId::Berlin) =>
+        {
+            WARM_ACCOUNT_ACCESS_GAS
+        }
+        InstructionType::BALANCE if spec.is_enabled_in(SpecId::Istanbul) => 700,
+        InstructionType::BALANCE if spec.is_enabled_in(SpecId::TangerineWhistle) => 400,
+        InstructionType::BALANCE => 20,
+        InstructionType::EXTCODEHASH if spec.is_enabled_in(SpecId::Istanbul) => 700,
+        InstructionType::EXTCODEHASH => 400,
+        InstructionType::EXTCODESIZE | InstructionType::EXTCODECOPY
+            if spec.is_enabled_in(SpecId::TangerineWhistle) =>
+        {
+            700
+        }
+        InstructionType::EXTCODESIZE | InstructionType::EXTCODECOPY => 20,
+        InstructionType::CALL
+        | InstructionType::CALLCODE
+        | InstructionType::DELEGATECALL
+        | InstructionType::STATICCALL
+            if spec.is_enabled_in(SpecId::TangerineWhistle) =>
+        {
+            700
+        }
+        InstructionType::CALL
+        | InstructionType::CALLCODE
+        | InstructionType::DELEGATECALL
+        | InstructionType::STATICCALL => 40,
+        InstructionType::CREATE | InstructionType::CREATE2 => CREATE_GAS,
+        InstructionType::RETURN | InstructionType::REVERT => 0,
+        InstructionType::RJUMP => 2,
+        InstructionType::RJUMPI => 4,
+        InstructionType::CALLF => 5,
+        InstructionType::RETF => 3,
+        InstructionType::SELFDESTRUCT if spec.is_enabled_in(SpecId::TangerineWhistle) => {
+            SELFDESTRUCT_GAS
+        }
+        InstructionType::SELFDESTRUCT => 0,
+    }
+}
+
+pub fn to_word_size(size: u64) -> u64 {
+    size.div_ceil(WORD_SIZE)
+}
+
+pub fn memory_cost(size: u64) -> u64 {
+    let words = to_word_size(size);
+
+    // NOTE: saturates so that oversized offsets end up as out of gas
+    MEMORY_WORD_GAS
+        .saturating_mul(words)
+        .saturating_add(words.saturating_mul(words) / QUADRATIC_DENOMINATOR)
+}
+
+pub fn memory_expansion_cost(old_size: u64, new_size: u64) -> u64 {
+    memory_cost(new_size).saturating_sub(memory_cost(old_size))
+}
+
+pub fn copy_cost(size: u64) -> u64 {
+    COPY_WORD_GAS * to_word_size(size)
+}
+
+pub fn keccak256_cost(size: u64) -> u64 {
+    KECCAK256_WORD_GAS * to_word_size(size)
+}
+
+/// Returns the cost per exponent byte, which was raised in Spurious Dragon (EIP-160).
+pub fn exp_cost(exponent: Bytes32, spec: SpecId) -> u64 {
+    let leading_zeroes = exponent.0.iter().take_while(|byte| **byte == 0).count();
+    let byte_gas = if spec.is_enabled_in(SpecId::SpuriousDragon) {
+        EXP_BYTE_GAS
+    } else {
+        EXP_BYTE_GAS_FRONTIER
+    };
+
+    byte_gas * (32 - leading_zeroes) as u64
+}
+
+/// Returns the surcharge on top of the warm access cost for touching an account (EIP-2929).
+pub fn cold_account_access_cost(is_cold: bool) -> u64 {
+    if is_cold {
+        COLD_ACCOUNT_ACCESS_GAS - WARM_ACCOUNT_ACCESS_GAS
+    } else {
+        0
+    }
+}
+
+/// Returns the surcharge on top of the warm read cost for loading a storage slot (EIP-2929).
+pub fn cold_sload_cost(is_cold: bool) -> u64 {
+    if is_cold {
+        COLD_SLOAD_GAS - WARM_STORAGE_READ_GAS
+    } else {
+        0
+    }
+}
+
+/// Returns the intrinsic cost of the addresses and storage keys of an access list (EIP-2930).
+pub fn access_list_cost(addresses: u64, storage_keys: u64) -> u64 {
+    ACCESS_LIST_ADDRESS_GAS * addresses + ACCESS_LIST_STORAGE_KEY_GAS * storage_keys
+}
+
+/// Returns the maximum gas that can be forwarded to a sub-call (EIP-150).
+pub fn all_but_one_64th(gas: u64) -> u64 {
+    gas - gas / 64
+}
+
+pub fn initcode_cost(size: u64) -> u64 {
+    INITCODE_WORD_GAS * to_word_size(size)
+}
+
+pub fn code_deposit_cost(size: u64) -> u64 {
+    CODE_DEPOSIT_GAS * size
+}
+
+/// Returns the gas charged before a transaction executes: the base cost, the calldata
+/// cost (EIP-2028 made non-zero bytes cheaper) and the creation cost (EIP-2, EIP-3860).
+pub fn intrinsic_cost(data: &[u8], is_create: bool, spec: SpecId) -> u64 {
+    let non_zero_byte_gas = if spec.is_enabled_in(SpecId::Istanbul) {
+        TX_DATA_NON_ZERO_GAS
+    } else {
+        TX_DATA_NON_ZERO_GAS_FRONTIER
+    };
+
+    let zeroes = data.iter().filter(|byte| **byte == 0).count() as u64;
+    let mut cost =
+        TX_BASE_GAS + TX_DATA_ZERO_GAS * zeroes + non_zero_byte_gas * (data.len() as u64 - zeroes);
+
+    if is_create {
+        if spec.is_enabled_in(SpecId::Homestead) {
+            cost += TX_CREATE_GAS;
+        }
+
+        if spec.is_enabled_in(SpecId::Shanghai) {
+            cost += initcode_cost(data.len() as u64);
+        }
+    }
+
+    cost
+}
+
+/// Caps the refund to a fraction of the used gas, which is a fifth since London (EIP-3529).
+pub fn capped_refund(spec: SpecId, used: u64, refunded: i64) -> u64 {
+    let quotient = if spec.is_enabled_in(SpecId::London) {
+        5
+    } else {
+        2
+    };
+
+    (refunded.max(0) as u64).min(used / quotient)
+}
+
+pub fn log_cost(size: u64) -> u64 {
+    LOG_DATA_GAS * size
+}
+
+/// Prices SSTORE from the value of the slot at the start of the transaction, its
+/// current value and the new one. Since Istanbul, writes to a slot which is already
+/// dirty in the transaction only pay a warm read (EIP-2200, EIP-2929).
+pub fn sstore_cost(original: Bytes32, current: Bytes32, new: Bytes32, spec: SpecId) -> u64 {
+    // NOTE: Constantinople net gas metering (EIP-1283) was dropped by Petersburg
+    if !spec.is_enabled_in(SpecId::Istanbul) {
+        return if current == Bytes32::from(0) && new != Bytes32::from(0) {
+            SSTORE_SET_GAS
+        } else {
+            SSTORE_RESET_GAS_FRONTIER
+        };
+    }
+
+    let (read_gas, reset_gas) = sstore_gas(spec);
+
+    if current == new || original != current {
+        read_gas
+    } else if original == Bytes32::from(0) {
+        SSTORE_SET_GAS
+    } else {
+        reset_gas
+    }
+}
+
+/// Returns the change of the refund counter for an SSTORE. Clearing a slot is refunded,
+/// restoring a dirty slot to its original value gets back all but a warm read (EIP-2200),
+/// and the clearing refund is lowered since London (EIP-3529).
+pub fn sstore_refund(original: Bytes32, current: Bytes32, new: Bytes32, spec: SpecId) -> i64 {
+    let zero = Bytes32::from(0);
+
+    if !spec.is_enabled_in(SpecId::Istanbul) {
+        return if current != zero && new == zero {
+            SSTORE_CLEARS_REFUND_FRONTIER
+        } else {
+            0
+        };
+    }
+
+    let clears_refund = if spec.is_enabled_in(SpecId::London) {
+        SSTORE_CLEARS_REFUND
+    } else {
+        SSTORE_CLEARS_REFUND_FRONTIER
+    };
+
+    if current == new {
+        return 0;
+    }
+
+    if original == current {
+        return if original != zero && new == zero {
+            clears_refund
+        } else {
+            0
+        };
+    }
+
+    let mut refund = 0;
+
+    if original != zero {
+        if current == zero {
+            refund -= clears_refund;
+        }
+        if new == zero {
+            refund += clears_refund;
+        }
+    }
+
+    if original == new {
+        let (read_gas, reset_gas) = sstore_gas(spec);
+        let write_gas = if original == zero {
+            SSTORE_SET_GAS
+        } else {
+            reset_gas
+        };
+
+        refund += (write_gas - read_gas) as i64;
+    }
+
+    refund
+}
+
+/// Returns the warm read and the reset cost of SSTORE since Istanbul.
+fn sstore_gas(spec: SpecId) -> (u64, u64) {
+    if spec.is_enabled_in(SpecId::Berlin) {
+        (WARM_STORAGE_READ_GAS, SSTORE_RESET_GAS)
+    } else {
+        (SLOAD_GAS_ISTANBUL, SSTORE_RESET_GAS_FRONTIER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_charges_gas() -> Result<(), GasError> {
+        let mut gas = Gas::new(100);
+
+        gas.charge(30)?;
+
+        assert_eq!(gas.used(), 30);
+        assert_eq!(gas.remaining(), 70);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_charge_more_than_remaining_returns_out_of_gas_error() {
+        let mut gas = Gas::new(10);
+
+        let result = gas.charge(11);
+
+        assert!(matches!(result, Err(GasError::OutOfGas)));
+        assert_eq!(gas.remaining(), 0);
+    }
+
+    #[test]
+    fn test_memory_expansion_cost() {
+        // NOTE: first word costs 3, 32 words cost 3 * 32 + 32**2 / 512 = 98
+        assert_eq!(memory_expansion_cost(0, 32), 3);
+        assert_eq!(memory_expansion_cost(0, 33), 6);
+        assert_eq!(memory_expansion_cost(32, 64), 3);
+        assert_eq!(memory_expansion_cost(0, 1024), 98);
+        assert_eq!(memory_expansion_cost(64, 32), 0);
+    }
+
+    #[test]
+    fn test_copy_and_keccak256_costs() {
+        assert_eq!(copy_cost(0), 0);
+        assert_eq!(copy_cost(33), 6);
+        assert_eq!(keccak256_cost(5), 6);
+        assert_eq!(keccak256_cost(64), 12);
+    }
+
+    #[test]
+    fn test_exp_cost() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(exp_cost(Bytes32::from(0), SpecId::Cancun), 0);
+        assert_eq!(exp_cost(Bytes32::from(0xff), SpecId::Cancun), 50);
+        assert_eq!(exp_cost("0100".parse::<Bytes32>()?, SpecId::Cancun), 100);
+        assert_eq!(exp_cost("0100".parse::<Bytes32>()?, SpecId::Homestead), 20);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_costs() {
+        assert_eq!(cold_account_access_cost(true), 2_500);
+        assert_eq!(cold_account_access_cost(false), 0);
+        assert_eq!(cold_sload_cost(true), 2_000);
+        assert_eq!(cold_sload_cost(false), 0);
+        assert_eq!(access_list_cost(2, 3), 10_500);
+        assert_eq!(all_but_one_64th(6_400), 6_300);
+        assert_eq!(all_but_one_64th(63), 63);
+    }
+
+    #[test]
+    fn test_create_costs() {
+        assert_eq!(initcode_cost(33), 4);
+        assert_eq!(code_deposit_cost(10), 2_000);
+    }
+
+    #[test]
+    fn test_intrinsic_cost() {
+        assert_eq!(intrinsic_cost(&[], false, SpecId::Cancun), 21_000);
+        assert_eq!(intrinsic_cost(&[0, 1, 0], false, SpecId::Cancun), 21_024);
+        assert_eq!(intrinsic_cost(&[0, 1, 0], false, SpecId::Byzantium), 21_076);
+        assert_eq!(intrinsic_cost(&[1; 33], true, SpecId::Cancun), 53_532);
+        assert_eq!(intrinsic_cost(&[1; 33], true, SpecId::London), 53_528);
+        assert_eq!(intrinsic_cost(&[], true, SpecId::Frontier), 21_000);
+    }
+
+    #[test]
+    fn test_capped_refund() {
+        assert_eq!(capped_refund(SpecId::Berlin, 30_000, 24_000), 15_000);
+        assert_eq!(capped_refund(SpecId::London, 30_000, 24_000), 6_000);
+        assert_eq!(capped_refund(SpecId::Cancun, 30_000, 0), 0);
+    }
+
+    #[test]
+    fn test_sstore_cost() {
+        let (zero, one, two) = (Bytes32::from(0), Bytes32::from(1), Bytes32::from(2));
+
+        assert_eq!(sstore_cost(zero, zero, one, SpecId::Cancun), 20_000);
+        assert_eq!(sstore_cost(one, one, two, SpecId::Cancun), 2_900);
+        assert_eq!(sstore_cost(one, one, one, SpecId::Cancun), 100);
+        assert_eq!(sstore_cost(one, one, two, SpecId::Istanbul), 5_000);
+        assert_eq!(sstore_cost(one, one, one, SpecId::Istanbul), 800);
+        assert_eq!(sstore_cost(one, one, one, SpecId::Byzantium), 5_000);
+
+        // NOTE: a dirty slot only pays a warm read since Istanbul
+        assert_eq!(sstore_cost(zero, one, two, SpecId::Cancun), 100);
+        assert_eq!(sstore_cost(one, two, zero, SpecId::Istanbul), 800);
+        assert_eq!(sstore_cost(zero, one, two, SpecId::Petersburg), 5_000);
+        assert_eq!(sstore_cost(one, zero, two, SpecId::Petersburg), 20_000);
+    }
+
+    #[test]
+    fn test_sstore_refund() {
+        let (zero, one, two) = (Bytes32::from(0), Bytes32::from(1), Bytes32::from(2));
+
+        assert_eq!(sstore_refund(one, one, zero, SpecId::Byzantium), 15_000);
+        assert_eq!(sstore_refund(one, one, zero, SpecId::Berlin), 15_000);
+        assert_eq!(sstore_refund(one, one, zero, SpecId::London), 4_800);
+        assert_eq!(sstore_refund(zero, zero, one, SpecId::Cancun), 0);
+
+        // NOTE: restoring a dirty slot refunds the write down to a warm read
+        assert_eq!(sstore_refund(zero, one, zero, SpecId::Cancun), 19_900);
+        assert_eq!(sstore_refund(one, two, one, SpecId::Cancun), 2_800);
+        assert_eq!(sstore_refund(one, two, one, SpecId::Istanbul), 4_200);
+
+        // NOTE: writing to a cleared slot takes the clearing refund back
+        assert_eq!(sstore_refund(one, zero, two, SpecId::Cancun), -4_800);
+        assert_eq!(
+            sstore_refund(one, zero, one, SpecId::Cancun),
+            -4_800 + 2_800
+        );
+    }
+
+    #[test]
+    fn test_static_cost_per_spec() {
+        let sload = InstructionType::SLOAD;
+        assert_eq!(static_cost(&sload, SpecId::Frontier), 50);
+        assert_eq!(static_cost(&sload, SpecId::TangerineWhistle), 200);
+        assert_eq!(static_cost(&sload, SpecId::Istanbul), 800);
+        assert_eq!(static_cost(&sload, SpecId::Berlin), 100);
+
+        let balance = InstructionType::BALANCE;
+        assert_eq!(static_cost(&balance, SpecId::Frontier), 20);
+        assert_eq!(static_cost(&balance, SpecId::Byzantium), 400);
+        assert_eq!(static_cost(&balance, SpecId::Istanbul), 700);
+        assert_eq!(static_cost(&balance, SpecId::London), 100);
+
+        let call = InstructionType::CALL;
+        assert_eq!(static_cost(&call, SpecId::Homestead), 40);
+        assert_eq!(static_cost(&call, SpecId::Istanbul), 700);
+        assert_eq!(static_cost(&call, SpecId::Cancun), 100);
+
+        let selfdestruct = InstructionType::SELFDESTRUCT;
+        assert_eq!(static_cost(&selfdestruct, SpecId::Homestead), 0);
+        assert_eq!(static_cost(&selfdestruct, SpecId::Cancun), 5_000);
+    }
+}
@@ -1,5 +1,5 @@
-use super::utils::errors::InstructionError;
-use std::str::FromStr;
+use super::{spec::SpecId, utils::errors::InstructionError};
+use std::{fmt::Display, str::FromStr};
 
 pub struct Instruction<'a> {
     pub r#type: InstructionType,
@@ -26,14 +26,182 @@ pub enum InstructionType {
     NOT = 0x19,
     BYTE = 0x1a,
     KECCAK256 = 0x20,
+    ADDRESS = 0x30,
+    BALANCE = 0x31,
+    ORIGIN = 0x32,
+    CALLER = 0x33,
+    CALLVALUE = 0x34,
+    CALLDATALOAD = 0x35,
+    CALLDATASIZE = 0x36,
+    CALLDATACOPY = 0x37,
+    CODESIZE = 0x38,
+    CODECOPY = 0x39,
+    GASPRICE = 0x3a,
+    EXTCODESIZE = 0x3b,
+    EXTCODECOPY = 0x3c,
+    RETURNDATASIZE = 0x3d,
+    RETURNDATACOPY = 0x3e,
+    EXTCODEHASH = 0x3f,
+    BLOCKHASH = 0x40,
+    COINBASE = 0x41,
+    TIMESTAMP = 0x42,
+    NUMBER = 0x43,
+    PREVRANDAO = 0x44,
+    GASLIMIT = 0x45,
+    CHAINID = 0x46,
+    SELFBALANCE = 0x47,
+    BASEFEE = 0x48,
     POP = 0x50,
     MLOAD = 0x51,
     MSTORE = 0x52,
     SLOAD = 0x54,
     SSTORE = 0x55,
+    GAS = 0x5a,
     PUSH(u8),
     DUP(u8),
     SWAP(u8),
+    LOG(u8),
+    RJUMP = 0xe0,
+    RJUMPI = 0xe1,
+    CALLF = 0xe3,
+    RETF = 0xe4,
+    CREATE = 0xf0,
+    CALL = 0xf1,
+    CALLCODE = 0xf2,
+    RETURN = 0xf3,
+    DELEGATECALL = 0xf4,
+    CREATE2 = 0xf5,
+    STATICCALL = 0xfa,
+    REVERT = 0xfd,
+    SELFDESTRUCT = 0xff,
+}
+
+impl InstructionType {
+    /// Returns the hard fork which introduced the opcode.
+    pub fn introduced_in(&self) -> SpecId {
+        match self {
+            InstructionType::DELEGATECALL => SpecId::Homestead,
+            InstructionType::RETURNDATASIZE
+            | InstructionType::RETURNDATACOPY
+            | InstructionType::STATICCALL
+            | InstructionType::REVERT => SpecId::Byzantium,
+            InstructionType::CREATE2 | InstructionType::EXTCODEHASH => SpecId::Constantinople,
+            InstructionType::CHAINID | InstructionType::SELFBALANCE => SpecId::Istanbul,
+            InstructionType::BASEFEE => SpecId::London,
+            InstructionType::PUSH(0) => SpecId::Shanghai,
+            InstructionType::RJUMP
+            | InstructionType::RJUMPI
+            | InstructionType::CALLF
+            | InstructionType::RETF => SpecId::Prague,
+            _ => SpecId::Frontier,
+        }
+    }
+
+    pub fn is_enabled_in(&self, spec: SpecId) -> bool {
+        spec.is_enabled_in(self.introduced_in())
+    }
+
+    /// Returns true for the opcodes which are only valid inside an EOF container.
+    pub fn is_eof_only(&self) -> bool {
+        matches!(
+            self,
+            InstructionType::RJUMP
+                | InstructionType::RJUMPI
+                | InstructionType::CALLF
+                | InstructionType::RETF
+        )
+    }
+
+    /// Returns the number of immediate bytes following the opcode.
+    pub fn immediate_size(&self) -> usize {
+        match self {
+            InstructionType::PUSH(size) => *size as usize,
+            InstructionType::RJUMP | InstructionType::RJUMPI | InstructionType::CALLF => 2,
+            _ => 0,
+        }
+    }
+
+    /// Returns true if the execution cannot continue with the next instruction.
+    pub fn is_terminating(&self) -> bool {
+        matches!(
+            self,
+            InstructionType::STOP
+                | InstructionType::RETURN
+                | InstructionType::REVERT
+                | InstructionType::RETF
+                | InstructionType::SELFDESTRUCT
+        )
+    }
+
+    /// Returns the number of stack items the opcode consumes and produces. CALLF and
+    /// RETF depend on the called section and are resolved by the EOF validation.
+    pub fn stack_io(&self) -> (u16, u16) {
+        match self {
+            InstructionType::STOP
+            | InstructionType::RJUMP
+            | InstructionType::CALLF
+            | InstructionType::RETF => (0, 0),
+            InstructionType::ADD
+            | InstructionType::MUL
+            | InstructionType::SUB
+            | InstructionType::DIV
+            | InstructionType::MOD
+            | InstructionType::EXP
+            | InstructionType::LT
+            | InstructionType::GT
+            | InstructionType::EQ
+            | InstructionType::AND
+            | InstructionType::OR
+            | InstructionType::XOR
+            | InstructionType::BYTE
+            | InstructionType::KECCAK256 => (2, 1),
+            InstructionType::ISZERO
+            | InstructionType::NOT
+            | InstructionType::BALANCE
+            | InstructionType::CALLDATALOAD
+            | InstructionType::EXTCODESIZE
+            | InstructionType::EXTCODEHASH
+            | InstructionType::BLOCKHASH
+            | InstructionType::MLOAD
+            | InstructionType::SLOAD => (1, 1),
+            InstructionType::ADDRESS
+            | InstructionType::ORIGIN
+            | InstructionType::CALLER
+            | InstructionType::CALLVALUE
+            | InstructionType::CALLDATASIZE
+            | InstructionType::CODESIZE
+            | InstructionType::GASPRICE
+            | InstructionType::RETURNDATASIZE
+            | InstructionType::COINBASE
+            | InstructionType::TIMESTAMP
+            | InstructionType::NUMBER
+            | InstructionType::PREVRANDAO
+            | InstructionType::GASLIMIT
+            | InstructionType::CHAINID
+            | InstructionType::SELFBALANCE
+            | InstructionType::BASEFEE
+            | InstructionType::GAS
+            | InstructionType::PUSH(_) => (0, 1),
+            InstructionType::CALLDATACOPY
+            | InstructionType::CODECOPY
+            | InstructionType::RETURNDATACOPY => (3, 0),
+            InstructionType::EXTCODECOPY => (4, 0),
+            InstructionType::POP | InstructionType::RJUMPI | InstructionType::SELFDESTRUCT => {
+                (1, 0)
+            }
+            InstructionType::MSTORE
+            | InstructionType::SSTORE
+            | InstructionType::RETURN
+            | InstructionType::REVERT => (2, 0),
+            InstructionType::DUP(size) => (*size as u16, *size as u16 + 1),
+            InstructionType::SWAP(size) => (*size as u16 + 1, *size as u16 + 1),
+            InstructionType::LOG(topics) => (*topics as u16 + 2, 0),
+            InstructionType::CREATE => (3, 1),
+            InstructionType::CREATE2 => (4, 1),
+            InstructionType::CALL | InstructionType::CALLCODE => (7, 1),
+            InstructionType::DELEGATECALL | InstructionType::STATICCALL => (6, 1),
+        }
+    }
 }
 
 impl FromStr for InstructionType {
@@ -59,14 +227,54 @@ impl FromStr for InstructionType {
             0x19 => Ok(InstructionType::NOT),
             0x1a => Ok(InstructionType::BYTE),
             0x20 => Ok(InstructionType::KECCAK256),
+            0x30 => Ok(InstructionType::ADDRESS),
+            0x31 => Ok(InstructionType::BALANCE),
+            0x32 => Ok(InstructionType::ORIGIN),
+            0x33 => Ok(InstructionType::CALLER),
+            0x34 => Ok(InstructionType::CALLVALUE),
+            0x35 => Ok(InstructionType::CALLDATALOAD),
+            0x36 => Ok(InstructionType::CALLDATASIZE),
+            0x37 => Ok(InstructionType::CALLDATACOPY),
+            0x38 => Ok(InstructionType::CODESIZE),
+            0x39 => Ok(InstructionType::CODECOPY),
+            0x3a => Ok(InstructionType::GASPRICE),
+            0x3b => Ok(InstructionType::EXTCODESIZE),
+            0x3c => Ok(InstructionType::EXTCODECOPY),
+            0x3d => Ok(InstructionType::RETURNDATASIZE),
+            0x3e => Ok(InstructionType::RETURNDATACOPY),
+            0x3f => Ok(InstructionType::EXTCODEHASH),
+            0x40 => Ok(InstructionType::BLOCKHASH),
+            0x41 => Ok(InstructionType::COINBASE),
+            0x42 => Ok(InstructionType::TIMESTAMP),
+            0x43 => Ok(InstructionType::NUMBER),
+            0x44 => Ok(InstructionType::PREVRANDAO),
+            0x45 => Ok(InstructionType::GASLIMIT),
+            0x46 => Ok(InstructionType::CHAINID),
+            0x47 => Ok(InstructionType::SELFBALANCE),
+            0x48 => Ok(InstructionType::BASEFEE),
             0x50 => Ok(InstructionType::POP),
             0x51 => Ok(InstructionType::MLOAD),
             0x52 => Ok(InstructionType::MSTORE),
             0x54 => Ok(InstructionType::SLOAD),
             0x55 => Ok(InstructionType::SSTORE),
+            0x5a => Ok(InstructionType::GAS),
             0x5f..=0x7f => Ok(InstructionType::PUSH((tmp % 0x5f) as u8)),
             0x80..=0x8f => Ok(InstructionType::DUP(((tmp % 0x80) + 1) as u8)),
             0x90..=0x9f => Ok(InstructionType::SWAP(((tmp % 0x90) + 1) as u8)),
+            0xa0..=0xa4 => Ok(InstructionType::LOG((tmp % 0xa0) as u8)),
+            0xe0 => Ok(InstructionType::RJUMP),
+            0xe1 => Ok(InstructionType::RJUMPI),
+            0xe3 => Ok(InstructionType::CALLF),
+            0xe4 => Ok(InstructionType::RETF),
+            0xf0 => Ok(InstructionType::CREATE),
+            0xf1 => Ok(InstructionType::CALL),
+            0xf2 => Ok(InstructionType::CALLCODE),
+            0xf3 => Ok(InstructionType::RETURN),
+            0xf4 => Ok(InstructionType::DELEGATECALL),
+            0xf5 => Ok(InstructionType::CREATE2),
+            0xfa => Ok(InstructionType::STATICCALL),
+            0xfd => Ok(InstructionType::REVERT),
+            0xff => Ok(InstructionType::SELFDESTRUCT),
             _ => {
                 let mut array = [0; 2];
                 let bytes = s.as_bytes();
@@ -78,3 +286,15 @@ impl FromStr for InstructionType {
         }
     }
 }
+
+impl Display for InstructionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstructionType::PUSH(size) => write!(f, "PUSH{size}"),
+            InstructionType::DUP(size) => write!(f, "DUP{size}"),
+            InstructionType::SWAP(size) => write!(f, "SWAP{size}"),
+            InstructionType::LOG(size) => write!(f, "LOG{size}"),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
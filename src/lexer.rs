@@ -37,6 +37,17 @@ impl<'a> Lexer<'a> {
         self.read_position += 1;
     }
 
+    /// Moves the lexer to the given byte offset of the bytecode.
+    pub fn seek(&mut self, offset: usize) {
+        self.read_position = 2 * offset as u64;
+        self.read_char();
+    }
+
+    /// Returns the byte offset of the next byte to be read.
+    pub fn byte_offset(&self) -> usize {
+        (self.position / 2) as usize
+    }
+
     pub fn next_byte(&mut self) -> Result<String, Box<dyn Error>> {
         let first_nibble = self.ch;
         self.read_char();
@@ -114,6 +125,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_seeks_to_byte_offset() -> Result<(), Box<dyn Error>> {
+        let mut lexer = Lexer::new("0x608011facddb")?;
+        lexer.read_char();
+
+        lexer.seek(3);
+        assert_eq!(lexer.byte_offset(), 3);
+        assert_eq!(lexer.next_byte()?, "fa");
+        assert_eq!(lexer.byte_offset(), 4);
+
+        lexer.seek(0);
+        assert_eq!(lexer.next_byte()?, "60");
+
+        Ok(())
+    }
+
     #[test]
     fn test_bytecode_contains_whitespace_returns_lexer_error() -> Result<(), Box<dyn Error>> {
         let bytecode = "0x60 80";
@@ -1,14 +1,24 @@
+pub mod env;
+pub mod eof;
+pub mod gas;
 pub mod instruction;
 pub mod lexer;
 pub mod memory;
+pub mod precompile;
+pub mod spec;
 pub mod stack;
+pub mod state;
 pub mod storage;
+pub mod transaction;
 pub mod vm;
 pub mod utils {
+    pub mod address;
     pub mod bytes32;
     pub mod cli;
+    pub mod crypto;
     pub mod errors;
     pub mod history;
+    pub mod rlp;
 }
 
 pub use instruction::Instruction;
@@ -1,11 +1,44 @@
 use std::error::Error;
 
 use clap::Parser;
-use cubipods::utils::cli::{AppBuilder, Args};
+use cubipods::utils::cli::{AppBuilder, Args, Command};
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
+    if let Some(Command::CreateAccessList) = args.command() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&args.create_access_list()?)?
+        );
+
+        return Ok(());
+    }
+
+    if args.is_replay() {
+        let (tx, result) = args.replay()?;
+        println!("Sender: {}\n{result}", tx.caller);
+
+        return Ok(());
+    }
+
+    if args.is_deploy() {
+        let (deployment, call) = args.deploy()?;
+        println!("Deployment:\n{deployment}");
+
+        if let Some(call) = call {
+            println!("\nCall:\n{call}");
+        }
+
+        return Ok(());
+    }
+
+    if args.is_transaction() {
+        println!("{}", args.transact()?);
+
+        return Ok(());
+    }
+
     let mut vm = args.build()?;
 
     vm.run()?;
@@ -15,5 +48,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         vm.history.analyze(&vm);
     }
 
+    if let Some(format) = vm.profile {
+        vm.history.summarize_profile(format)?;
+    }
+
     Ok(())
 }
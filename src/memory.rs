@@ -1,8 +1,12 @@
-use crate::utils::bytes32::Bytes32;
+use crate::{
+    gas,
+    utils::{bytes32::Bytes32, errors::GasError},
+};
 
 #[derive(Debug)]
 pub struct Memory {
     pub heap: Vec<u8>,
+    expansion_cost: u64,
 }
 
 impl Default for Memory {
@@ -13,11 +17,27 @@ impl Default for Memory {
 
 impl Memory {
     pub fn new() -> Self {
-        Self { heap: vec![0; 0] }
+        Self {
+            heap: vec![0; 0],
+            expansion_cost: 0,
+        }
     }
 
     fn extend(&mut self, size: usize) {
+        let old_size = self.heap.len() as u64;
         self.heap.extend(vec![0; size]);
+
+        self.expansion_cost += gas::memory_expansion_cost(old_size, self.heap.len() as u64);
+    }
+
+    /// Returns the gas charged for memory growth since the last call and resets it.
+    pub fn take_expansion_cost(&mut self) -> u64 {
+        std::mem::take(&mut self.expansion_cost)
+    }
+
+    /// Returns the gas charged for memory growth since the last call without resetting it.
+    pub fn expansion_cost(&self) -> u64 {
+        self.expansion_cost
     }
 
     /// Documentation
@@ -25,12 +45,12 @@ impl Memory {
     /// # Safety
     ///
     /// As Memory::mload, it loads data from given location pointer.
-    pub unsafe fn mload(&mut self, location: Bytes32) -> Bytes32 {
-        let location: usize = location.try_into().unwrap();
-        let extended_location = location + 32;
+    pub unsafe fn mload(&mut self, location: Bytes32) -> Result<Bytes32, GasError> {
+        let location = location.saturating_to_usize();
+        let extended_location = location.checked_add(32).ok_or(GasError::OutOfGas)?;
 
         if extended_location > self.heap.len() {
-            if location % 32 == 0 {
+            if location.is_multiple_of(32) {
                 self.extend(extended_location - self.heap.len());
             } else {
                 self.extend(extended_location + (location % 32) - self.heap.len());
@@ -39,7 +59,7 @@ impl Memory {
 
         let ptr = self.heap.as_ptr().add(location) as *const Bytes32;
 
-        unsafe { *ptr }
+        unsafe { Ok(*ptr) }
     }
 
     /// Documentation
@@ -47,9 +67,9 @@ impl Memory {
     /// # Safety
     ///
     /// As Memory::mstore, it stores given data to given location pointer.
-    pub unsafe fn mstore(&mut self, location: Bytes32, data: Bytes32) {
-        let location: usize = location.try_into().unwrap();
-        let extended_location = location + 32;
+    pub unsafe fn mstore(&mut self, location: Bytes32, data: Bytes32) -> Result<(), GasError> {
+        let location = location.saturating_to_usize();
+        let extended_location = location.checked_add(32).ok_or(GasError::OutOfGas)?;
 
         if extended_location > self.heap.len() {
             self.extend(extended_location - self.heap.len());
@@ -58,6 +78,8 @@ impl Memory {
         let ptr = self.heap.as_mut_ptr().add(location) as *mut [u8; 32];
 
         *ptr = data.0;
+
+        Ok(())
     }
 
     /// Documentation
@@ -73,12 +95,42 @@ impl Memory {
         unsafe { *ptr }
     }
 
+    pub fn store_bytes(&mut self, location: usize, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        let extended_location = location + data.len();
+
+        if extended_location > self.heap.len() {
+            self.extend(extended_location - self.heap.len());
+        }
+
+        self.heap[location..extended_location].copy_from_slice(data);
+    }
+
+    pub fn load_bytes(&mut self, location: usize, size: usize) -> Vec<u8> {
+        if size == 0 {
+            return vec![];
+        }
+
+        let extended_location = location + size;
+
+        if extended_location > self.heap.len() {
+            self.extend(extended_location - self.heap.len());
+        }
+
+        self.heap[location..extended_location].to_vec()
+    }
+
     pub fn msize(&self) -> usize {
         self.heap.len()
     }
 }
 
 #[cfg(test)]
+// NOTE: the original tests compare against bool literals
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -108,9 +160,9 @@ mod tests {
 
         let result: Bytes32;
         unsafe {
-            memory.mstore(mem_location, data);
+            memory.mstore(mem_location, data).unwrap();
 
-            result = memory.mload(mem_location);
+            result = memory.mload(mem_location).unwrap();
         }
 
         assert_eq!(result, data);
@@ -126,12 +178,50 @@ mod tests {
 
         let result: Bytes32;
         unsafe {
-            memory.mstore(mem_location, data);
+            memory.mstore(mem_location, data).unwrap();
 
-            result = memory.mload(mem_location);
+            result = memory.mload(mem_location).unwrap();
         }
 
         assert_eq!(result, data);
         assert_eq!(memory.msize(), mem_upper_limit);
     }
+
+    #[test]
+    fn it_stores_bytes_in_memory() {
+        let mut memory = Memory::new();
+
+        memory.store_bytes(30, &[0xaa, 0xbb, 0xcc]);
+
+        assert_eq!(memory.msize(), 33);
+        assert_eq!(memory.heap[30..33], [0xaa, 0xbb, 0xcc]);
+
+        memory.store_bytes(100, &[]);
+
+        assert_eq!(memory.msize(), 33);
+    }
+
+    #[test]
+    fn it_loads_bytes_from_memory() {
+        let mut memory = Memory::new();
+
+        memory.store_bytes(0, &[0xaa, 0xbb]);
+
+        assert_eq!(memory.load_bytes(1, 3), vec![0xbb, 0, 0]);
+        assert_eq!(memory.msize(), 4);
+        assert_eq!(memory.load_bytes(100, 0), Vec::<u8>::new());
+        assert_eq!(memory.msize(), 4);
+    }
+
+    #[test]
+    fn it_charges_quadratic_expansion_cost() {
+        let mut memory = Memory::new();
+
+        memory.extend(32);
+        assert_eq!(memory.take_expansion_cost(), 3);
+        assert_eq!(memory.take_expansion_cost(), 0);
+
+        memory.extend(992);
+        assert_eq!(memory.take_expansion_cost(), 95);
+    }
 }
@@ -0,0 +1,711 @@
+use num_bigint::BigUint;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use substrate_bn::{AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+
+use crate::{
+    gas,
+    spec::SpecId,
+    utils::{address::Address, bytes32::Bytes32, crypto, errors::PrecompileError},
+    vm::read_padded,
+};
+
+pub const ECRECOVER_GAS: u64 = 3_000;
+pub const SHA256_BASE_GAS: u64 = 60;
+pub const SHA256_WORD_GAS: u64 = 12;
+pub const RIPEMD160_BASE_GAS: u64 = 600;
+pub const RIPEMD160_WORD_GAS: u64 = 120;
+pub const IDENTITY_BASE_GAS: u64 = 15;
+pub const IDENTITY_WORD_GAS: u64 = 3;
+pub const MODEXP_MIN_GAS: u64 = 200;
+pub const MODEXP_QUADRATIC_DIVISOR_BYZANTIUM: u128 = 20;
+pub const BN254_ADD_GAS: u64 = 150;
+pub const BN254_MUL_GAS: u64 = 6_000;
+pub const BN254_PAIRING_BASE_GAS: u64 = 45_000;
+pub const BN254_PAIRING_PAIR_GAS: u64 = 34_000;
+pub const BN254_ADD_GAS_BYZANTIUM: u64 = 500;
+pub const BN254_MUL_GAS_BYZANTIUM: u64 = 40_000;
+pub const BN254_PAIRING_BASE_GAS_BYZANTIUM: u64 = 100_000;
+pub const BN254_PAIRING_PAIR_GAS_BYZANTIUM: u64 = 80_000;
+pub const BLAKE2F_ROUND_GAS: u64 = 1;
+
+/// Size of a G1 point and a G2 point in the pairing input.
+const BN254_PAIR_SIZE: usize = 192;
+/// Size of rounds ++ h ++ m ++ t ++ f in the BLAKE2F input.
+const BLAKE2F_INPUT_SIZE: usize = 213;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PrecompileOutput {
+    pub gas_used: u64,
+    pub output: Vec<u8>,
+}
+
+pub type PrecompileResult = Result<PrecompileOutput, PrecompileError>;
+
+/// A precompiled contract takes the call data and the gas limit of the call.
+pub type Precompile = fn(&[u8], u64) -> PrecompileResult;
+
+/// Returns the precompiled contract at the given address if it is active in the spec.
+pub fn get(address: &Address, spec: SpecId) -> Option<Precompile> {
+    if address.0[..19].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+
+    let byzantium = spec.is_enabled_in(SpecId::Byzantium);
+    let istanbul = spec.is_enabled_in(SpecId::Istanbul);
+
+    match address.0[19] {
+        0x01 => Some(ecrecover),
+        0x02 => Some(sha256),
+        0x03 => Some(ripemd160),
+        0x04 => Some(identity),
+        0x05 if spec.is_enabled_in(SpecId::Berlin) => Some(modexp),
+        0x05 if byzantium => Some(modexp_byzantium),
+        0x06 if istanbul => Some(bn254_add),
+        0x06 if byzantium => Some(bn254_add_byzantium),
+        0x07 if istanbul => Some(bn254_mul),
+        0x07 if byzantium => Some(bn254_mul_byzantium),
+        0x08 if istanbul => Some(bn254_pairing),
+        0x08 if byzantium => Some(bn254_pairing_byzantium),
+        0x09 if istanbul => Some(blake2f),
+        _ => None,
+    }
+}
+
+/// Returns the addresses of all precompiled contracts, which are warm from the start (EIP-2929).
+pub fn addresses(spec: SpecId) -> impl Iterator<Item = Address> {
+    (0x01..=0x0a)
+        .map(Address::from)
+        .filter(move |address| get(address, spec).is_some())
+}
+
+fn charge(cost: u64, gas_limit: u64) -> Result<u64, PrecompileError> {
+    if cost > gas_limit {
+        return Err(PrecompileError::OutOfGas);
+    }
+
+    Ok(cost)
+}
+
+fn word_cost(base: u64, per_word: u64, size: usize) -> u64 {
+    base + per_word * gas::to_word_size(size as u64)
+}
+
+/// Recovers the signer address from hash ++ v ++ r ++ s. Invalid signatures
+/// succeed with an empty output.
+pub fn ecrecover(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let gas_used = charge(ECRECOVER_GAS, gas_limit)?;
+
+    let input = read_padded(input, 0, 128);
+
+    let output = match recover_address(&input) {
+        Some(address) => Bytes32::from(address).0.to_vec(),
+        None => vec![],
+    };
+
+    Ok(PrecompileOutput { gas_used, output })
+}
+
+fn recover_address(input: &[u8]) -> Option<Address> {
+    let hash = Bytes32(input[..32].try_into().unwrap());
+    let v = Bytes32(input[32..64].try_into().unwrap());
+
+    let recovery_id = if v == Bytes32::from(27) {
+        0
+    } else if v == Bytes32::from(28) {
+        1
+    } else {
+        return None;
+    };
+
+    let public_key =
+        crypto::recover_public_key(&hash, input[64..128].try_into().unwrap(), recovery_id)?;
+
+    Some(crypto::public_key_to_address(&public_key))
+}
+
+pub fn sha256(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let gas_used = charge(
+        word_cost(SHA256_BASE_GAS, SHA256_WORD_GAS, input.len()),
+        gas_limit,
+    )?;
+
+    Ok(PrecompileOutput {
+        gas_used,
+        output: Sha256::digest(input).to_vec(),
+    })
+}
+
+pub fn ripemd160(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let gas_used = charge(
+        word_cost(RIPEMD160_BASE_GAS, RIPEMD160_WORD_GAS, input.len()),
+        gas_limit,
+    )?;
+
+    // NOTE: the 20 bytes hash is left-padded to a word
+    let mut output = vec![0; 12];
+    output.extend(Ripemd160::digest(input));
+
+    Ok(PrecompileOutput { gas_used, output })
+}
+
+pub fn identity(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    let gas_used = charge(
+        word_cost(IDENTITY_BASE_GAS, IDENTITY_WORD_GAS, input.len()),
+        gas_limit,
+    )?;
+
+    Ok(PrecompileOutput {
+        gas_used,
+        output: input.to_vec(),
+    })
+}
+
+/// Computes base**exp % mod where the input is laid out as
+/// base_len ++ exp_len ++ mod_len ++ base ++ exp ++ mod (EIP-198).
+pub fn modexp(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_modexp(input, gas_limit, modexp_cost)
+}
+
+/// MODEXP as priced before Berlin.
+pub fn modexp_byzantium(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_modexp(input, gas_limit, modexp_cost_byzantium)
+}
+
+fn run_modexp(
+    input: &[u8],
+    gas_limit: u64,
+    cost: fn(usize, usize, usize, &[u8]) -> u64,
+) -> PrecompileResult {
+    let read_length = |offset: usize| {
+        Bytes32(read_padded(input, offset, 32).try_into().unwrap()).saturating_to_usize()
+    };
+
+    let base_len = read_length(0);
+    let exp_len = read_length(32);
+    let mod_len = read_length(64);
+
+    let exp_offset = 96usize.saturating_add(base_len);
+    let exp_head = read_padded(input, exp_offset, exp_len.min(32));
+
+    let gas_used = charge(cost(base_len, exp_len, mod_len, &exp_head), gas_limit)?;
+
+    if mod_len == 0 {
+        return Ok(PrecompileOutput {
+            gas_used,
+            output: vec![],
+        });
+    }
+
+    let mod_offset = exp_offset.saturating_add(exp_len);
+
+    let base = BigUint::from_bytes_be(&read_padded(input, 96, base_len));
+    let exponent = BigUint::from_bytes_be(&read_padded(input, exp_offset, exp_len));
+    let modulus = BigUint::from_bytes_be(&read_padded(input, mod_offset, mod_len));
+
+    let result = if modulus == BigUint::ZERO {
+        vec![]
+    } else {
+        base.modpow(&exponent, &modulus).to_bytes_be()
+    };
+
+    let mut output = vec![0; mod_len - result.len()];
+    output.extend(result);
+
+    Ok(PrecompileOutput { gas_used, output })
+}
+
+/// Returns the MODEXP gas cost (EIP-2565).
+pub fn modexp_cost(base_len: usize, exp_len: usize, mod_len: usize, exp_head: &[u8]) -> u64 {
+    let words = (base_len.max(mod_len) as u128).div_ceil(8);
+    let complexity = words.saturating_mul(words);
+
+    let iterations = modexp_iterations(exp_len, exp_head);
+    let cost = complexity.saturating_mul(iterations.max(1)) / 3;
+
+    u64::try_from(cost).unwrap_or(u64::MAX).max(MODEXP_MIN_GAS)
+}
+
+/// Returns the MODEXP gas cost before Berlin (EIP-198).
+pub fn modexp_cost_byzantium(
+    base_len: usize,
+    exp_len: usize,
+    mod_len: usize,
+    exp_head: &[u8],
+) -> u64 {
+    let x = base_len.max(mod_len) as u128;
+    let complexity = if x <= 64 {
+        x * x
+    } else if x <= 1024 {
+        x * x / 4 + 96 * x - 3_072
+    } else {
+        (x * x / 16).saturating_add(480 * x) - 199_680
+    };
+
+    let iterations = modexp_iterations(exp_len, exp_head);
+    let cost = complexity.saturating_mul(iterations.max(1)) / MODEXP_QUADRATIC_DIVISOR_BYZANTIUM;
+
+    u64::try_from(cost).unwrap_or(u64::MAX)
+}
+
+/// Returns the adjusted exponent length, which approximates the number of squarings.
+fn modexp_iterations(exp_len: usize, exp_head: &[u8]) -> u128 {
+    let head_bits = BigUint::from_bytes_be(exp_head).bits().saturating_sub(1) as u128;
+
+    if exp_len <= 32 {
+        head_bits
+    } else {
+        8 * (exp_len as u128 - 32) + head_bits
+    }
+}
+
+/// Adds two points on the alt_bn128 curve (EIP-196).
+pub fn bn254_add(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_bn254_add(input, gas_limit, BN254_ADD_GAS)
+}
+
+/// BN254 addition as priced before Istanbul.
+pub fn bn254_add_byzantium(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_bn254_add(input, gas_limit, BN254_ADD_GAS_BYZANTIUM)
+}
+
+fn run_bn254_add(input: &[u8], gas_limit: u64, cost: u64) -> PrecompileResult {
+    let gas_used = charge(cost, gas_limit)?;
+
+    let input = read_padded(input, 0, 128);
+    let p1 = read_g1_point(&input[..64])?;
+    let p2 = read_g1_point(&input[64..128])?;
+
+    Ok(PrecompileOutput {
+        gas_used,
+        output: encode_g1_point(p1 + p2),
+    })
+}
+
+/// Multiplies a point on the alt_bn128 curve by a scalar (EIP-196).
+pub fn bn254_mul(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_bn254_mul(input, gas_limit, BN254_MUL_GAS)
+}
+
+/// BN254 scalar multiplication as priced before Istanbul.
+pub fn bn254_mul_byzantium(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_bn254_mul(input, gas_limit, BN254_MUL_GAS_BYZANTIUM)
+}
+
+fn run_bn254_mul(input: &[u8], gas_limit: u64, cost: u64) -> PrecompileResult {
+    let gas_used = charge(cost, gas_limit)?;
+
+    let input = read_padded(input, 0, 96);
+    let point = read_g1_point(&input[..64])?;
+    let scalar = Fr::from_slice(&input[64..96]).map_err(|_| PrecompileError::InvalidInput)?;
+
+    Ok(PrecompileOutput {
+        gas_used,
+        output: encode_g1_point(point * scalar),
+    })
+}
+
+/// Checks that the product of the pairings of the given G1 and G2 points is one (EIP-197).
+pub fn bn254_pairing(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_bn254_pairing(
+        input,
+        gas_limit,
+        BN254_PAIRING_BASE_GAS,
+        BN254_PAIRING_PAIR_GAS,
+    )
+}
+
+/// BN254 pairing check as priced before Istanbul.
+pub fn bn254_pairing_byzantium(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    run_bn254_pairing(
+        input,
+        gas_limit,
+        BN254_PAIRING_BASE_GAS_BYZANTIUM,
+        BN254_PAIRING_PAIR_GAS_BYZANTIUM,
+    )
+}
+
+fn run_bn254_pairing(
+    input: &[u8],
+    gas_limit: u64,
+    base_cost: u64,
+    pair_cost: u64,
+) -> PrecompileResult {
+    if !input.len().is_multiple_of(BN254_PAIR_SIZE) {
+        return Err(PrecompileError::InvalidInput);
+    }
+
+    let pairs = (input.len() / BN254_PAIR_SIZE) as u64;
+    let gas_used = charge(
+        base_cost.saturating_add(pairs.saturating_mul(pair_cost)),
+        gas_limit,
+    )?;
+
+    let mut points = Vec::with_capacity(pairs as usize);
+
+    for pair in input.chunks(BN254_PAIR_SIZE) {
+        points.push((read_g1_point(&pair[..64])?, read_g2_point(&pair[64..])?));
+    }
+
+    let success = substrate_bn::pairing_batch(&points) == Gt::one();
+
+    Ok(PrecompileOutput {
+        gas_used,
+        output: Bytes32::from(success).0.to_vec(),
+    })
+}
+
+/// Runs the BLAKE2b compression function F on rounds ++ h ++ m ++ t ++ f (EIP-152).
+pub fn blake2f(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if input.len() != BLAKE2F_INPUT_SIZE {
+        return Err(PrecompileError::InvalidInput);
+    }
+
+    let rounds = u32::from_be_bytes(input[..4].try_into().unwrap());
+    let gas_used = charge(BLAKE2F_ROUND_GAS * rounds as u64, gas_limit)?;
+
+    let f = match input[212] {
+        0 => false,
+        1 => true,
+        _ => return Err(PrecompileError::InvalidInput),
+    };
+
+    let read_words = |offset: usize, count: usize| -> Vec<u64> {
+        input[offset..offset + count * 8]
+            .chunks(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .collect()
+    };
+
+    let mut h: [u64; 8] = read_words(4, 8).try_into().unwrap();
+    let m: [u64; 16] = read_words(68, 16).try_into().unwrap();
+    let t: [u64; 2] = read_words(196, 2).try_into().unwrap();
+
+    crypto::blake2b_compress(rounds, &mut h, &m, t, f);
+
+    Ok(PrecompileOutput {
+        gas_used,
+        output: h.iter().flat_map(|word| word.to_le_bytes()).collect(),
+    })
+}
+
+fn read_fq(input: &[u8]) -> Result<Fq, PrecompileError> {
+    Fq::from_slice(input).map_err(|_| PrecompileError::InvalidInput)
+}
+
+/// Reads x ++ y, where (0, 0) is the point at infinity.
+fn read_g1_point(input: &[u8]) -> Result<G1, PrecompileError> {
+    let x = read_fq(&input[..32])?;
+    let y = read_fq(&input[32..64])?;
+
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1::zero());
+    }
+
+    AffineG1::new(x, y)
+        .map(G1::from)
+        .map_err(|_| PrecompileError::InvalidInput)
+}
+
+/// Reads x_imaginary ++ x_real ++ y_imaginary ++ y_real.
+fn read_g2_point(input: &[u8]) -> Result<G2, PrecompileError> {
+    let x = Fq2::new(read_fq(&input[32..64])?, read_fq(&input[..32])?);
+    let y = Fq2::new(read_fq(&input[96..128])?, read_fq(&input[64..96])?);
+
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2::zero());
+    }
+
+    AffineG2::new(x, y)
+        .map(G2::from)
+        .map_err(|_| PrecompileError::InvalidInput)
+}
+
+fn encode_g1_point(point: G1) -> Vec<u8> {
+    let mut output = vec![0; 64];
+
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point.x().to_big_endian(&mut output[..32]).unwrap();
+        point.y().to_big_endian(&mut output[32..]).unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_precompiles_by_address() {
+        assert!(get(&Address::from(0x04), SpecId::Cancun).is_some());
+        assert!(get(&Address::from(0x0b), SpecId::Cancun).is_none());
+        assert!(get(
+            &"0x0100000000000000000000000000000000000004"
+                .parse()
+                .unwrap(),
+            SpecId::Cancun
+        )
+        .is_none());
+        assert_eq!(addresses(SpecId::Cancun).count(), 9);
+    }
+
+    #[test]
+    fn it_activates_precompiles_per_spec() {
+        assert_eq!(addresses(SpecId::Homestead).count(), 4);
+        assert_eq!(addresses(SpecId::Byzantium).count(), 8);
+        assert_eq!(addresses(SpecId::Istanbul).count(), 9);
+        assert!(get(&Address::from(0x09), SpecId::Petersburg).is_none());
+    }
+
+    #[test]
+    fn it_runs_ecrecover_precompile() -> Result<(), Box<dyn std::error::Error>> {
+        let input = hex::decode(
+            "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e\
+             000000000000000000000000000000000000000000000000000000000000001b\
+             38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e\
+             789d1dd423d25f0772d2748d60f7e4b81bb14d086eba8e8e8efb6dcff8a4ae02",
+        )?;
+        let result = ecrecover(&input, 3_000)?;
+
+        assert_eq!(result.gas_used, 3_000);
+        assert_eq!(
+            hex::encode(result.output),
+            "000000000000000000000000ceaccac640adf55b2028469bd36ba501f28b699d"
+        );
+
+        // NOTE: v must be 27 or 28
+        let mut input = input;
+        input[63] = 0x1d;
+        let result = ecrecover(&input, 3_000)?;
+
+        assert!(result.output.is_empty());
+        assert_eq!(ecrecover(&[], 3_000)?.output, Vec::<u8>::new());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_sha256_precompile() -> Result<(), PrecompileError> {
+        let result = sha256(b"", 100)?;
+
+        assert_eq!(result.gas_used, 60);
+        assert_eq!(
+            hex::encode(result.output),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_ripemd160_precompile() -> Result<(), PrecompileError> {
+        let result = ripemd160(b"", 1000)?;
+
+        assert_eq!(result.gas_used, 600);
+        assert_eq!(
+            hex::encode(result.output),
+            "0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_identity_precompile() -> Result<(), PrecompileError> {
+        let input = vec![0xab; 33];
+        let result = identity(&input, 100)?;
+
+        assert_eq!(result.gas_used, 21);
+        assert_eq!(result.output, input);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_modexp_precompile() -> Result<(), Box<dyn std::error::Error>> {
+        // NOTE: 3**(p - 2) % p = 1 where p is the secp256k1 field prime (EIP-198)
+        let input = hex::decode(
+            "0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             03\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e\
+             fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        )?;
+        let result = modexp(&input, 10_000)?;
+
+        assert_eq!(result.gas_used, 1_360);
+        assert_eq!(result.output, Bytes32::from(1).0.to_vec());
+
+        // NOTE: 32**2 * 255 / 20 before Berlin
+        let result = modexp_byzantium(&input, 20_000)?;
+
+        assert_eq!(result.gas_used, 13_056);
+        assert_eq!(result.output, Bytes32::from(1).0.to_vec());
+
+        // NOTE: zero modulus returns zeroes
+        let input = hex::decode(
+            "0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0302",
+        )?;
+        let result = modexp(&input, 10_000)?;
+
+        assert_eq!(result.gas_used, 200);
+        assert_eq!(result.output, vec![0, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_bn254_add_and_mul_precompiles() -> Result<(), Box<dyn std::error::Error>> {
+        // NOTE: G + G and G * 2 are both 2G where G is the generator (1, 2)
+        let double = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                      15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+
+        let input = [G1_GENERATOR, G1_GENERATOR].concat();
+        let result = bn254_add(&hex::decode(input)?, 150)?;
+
+        assert_eq!(result.gas_used, 150);
+        assert_eq!(hex::encode(result.output), double);
+
+        let input = [G1_GENERATOR, &Bytes32::from(2).to_string()].concat();
+        let result = bn254_mul(&hex::decode(input)?, 6_000)?;
+
+        assert_eq!(result.gas_used, 6_000);
+        assert_eq!(hex::encode(result.output), double);
+
+        assert_eq!(bn254_add_byzantium(&[], 500)?.gas_used, 500);
+        assert_eq!(bn254_mul_byzantium(&[], 40_000)?.gas_used, 40_000);
+        assert_eq!(bn254_pairing_byzantium(&[], 100_000)?.gas_used, 100_000);
+        assert_eq!(
+            bn254_mul_byzantium(&[], 6_000),
+            Err(PrecompileError::OutOfGas)
+        );
+
+        // NOTE: empty input is the sum of two points at infinity
+        assert_eq!(bn254_add(&[], 150)?.output, vec![0; 64]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_bn254_pairing_precompile() -> Result<(), Box<dyn std::error::Error>> {
+        // NOTE: e(G1, G2) * e(-G1, G2) = 1
+        let input = [
+            G1_GENERATOR,
+            G2_GENERATOR,
+            G1_NEGATED_GENERATOR,
+            G2_GENERATOR,
+        ]
+        .concat();
+        let result = bn254_pairing(&hex::decode(input)?, 113_000)?;
+
+        assert_eq!(result.gas_used, 113_000);
+        assert_eq!(result.output, Bytes32::from(1).0.to_vec());
+
+        let input = [G1_GENERATOR, G2_GENERATOR, G1_GENERATOR, G2_GENERATOR].concat();
+        let result = bn254_pairing(&hex::decode(input)?, 113_000)?;
+
+        assert_eq!(result.output, Bytes32::from(0).0.to_vec());
+        assert_eq!(
+            bn254_pairing(&[], 45_000)?.output,
+            Bytes32::from(1).0.to_vec()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bn254_invalid_input_returns_precompile_error() -> Result<(), Box<dyn std::error::Error>>
+    {
+        // NOTE: (1, 3) is not on the curve
+        let input = hex::decode(format!("{}{}", Bytes32::from(1), Bytes32::from(3)))?;
+
+        assert_eq!(bn254_add(&input, 150), Err(PrecompileError::InvalidInput));
+        assert_eq!(bn254_mul(&input, 6_000), Err(PrecompileError::InvalidInput));
+        assert_eq!(
+            bn254_pairing(&[0; 191], 100_000),
+            Err(PrecompileError::InvalidInput)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_blake2f_precompile() -> Result<(), Box<dyn std::error::Error>> {
+        // NOTE: test vectors 4 and 5 from EIP-152
+        let mut input = hex::decode(BLAKE2F_INPUT)?;
+        let result = blake2f(&input, 12)?;
+
+        assert_eq!(result.gas_used, 12);
+        assert_eq!(
+            hex::encode(result.output),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+
+        input[212] = 0;
+        let result = blake2f(&input, 12)?;
+
+        assert_eq!(
+            hex::encode(result.output),
+            "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d28752\
+             98743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blake2f_invalid_input_returns_precompile_error(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut input = hex::decode(BLAKE2F_INPUT)?;
+
+        assert_eq!(
+            blake2f(&input[..212], 12),
+            Err(PrecompileError::InvalidInput)
+        );
+
+        input[212] = 2;
+        assert_eq!(blake2f(&input, 12), Err(PrecompileError::InvalidInput));
+        assert_eq!(
+            blake2f(&hex::decode(BLAKE2F_INPUT)?, 11),
+            Err(PrecompileError::OutOfGas)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_precompile_without_enough_gas_returns_precompile_error() {
+        assert_eq!(sha256(b"", 59), Err(PrecompileError::OutOfGas));
+        assert_eq!(
+            modexp(&[0xff; 96], u64::MAX - 1),
+            Err(PrecompileError::OutOfGas)
+        );
+    }
+
+    const G1_GENERATOR: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                                0000000000000000000000000000000000000000000000000000000000000002";
+
+    const G1_NEGATED_GENERATOR: &str =
+        "0000000000000000000000000000000000000000000000000000000000000001\
+         30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+
+    const G2_GENERATOR: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                                1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+                                090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+                                12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+
+    const BLAKE2F_INPUT: &str = "0000000c\
+        48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+        d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b\
+        6162630000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        0000000000000000000000000000000000000000000000000000000000000000\
+        0300000000000000000000000000000001";
+}
@@ -0,0 +1,54 @@
+use clap::ValueEnum;
+
+/// Ethereum hard forks in activation order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum SpecId {
+    Frontier,
+    Homestead,
+    TangerineWhistle,
+    SpuriousDragon,
+    Byzantium,
+    Constantinople,
+    Petersburg,
+    Istanbul,
+    Berlin,
+    London,
+    Merge,
+    Shanghai,
+    #[default]
+    Cancun,
+    /// Cancun rules plus EOF v1 containers
+    // NOTE: none of the other Prague changes are implemented
+    Prague,
+}
+
+impl SpecId {
+    /// Returns true if the rules of `fork` are active in this spec.
+    pub fn is_enabled_in(self, fork: SpecId) -> bool {
+        self >= fork
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_enables_earlier_forks() {
+        assert!(SpecId::Cancun.is_enabled_in(SpecId::London));
+        assert!(SpecId::London.is_enabled_in(SpecId::London));
+        assert!(!SpecId::Berlin.is_enabled_in(SpecId::London));
+        assert_eq!(SpecId::default(), SpecId::Cancun);
+    }
+
+    #[test]
+    fn it_parses_spec_names() -> Result<(), String> {
+        assert_eq!(
+            SpecId::from_str("tangerine-whistle", true)?,
+            SpecId::TangerineWhistle
+        );
+        assert_eq!(SpecId::from_str("Shanghai", true)?, SpecId::Shanghai);
+
+        Ok(())
+    }
+}
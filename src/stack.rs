@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use super::utils::errors::StackError;
 
-const STACK_SIZE_LIMIT: u16 = 1024;
+pub const STACK_SIZE_LIMIT: u16 = 1024;
 
 #[derive(Debug)]
 pub struct Stack<T> {
@@ -153,6 +153,8 @@ impl<T> StackNode<T> {
 }
 
 #[cfg(test)]
+// NOTE: the original tests compare against bool literals
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
@@ -351,7 +353,7 @@ mod tests {
     }
 
     // NOTE: Helper function
-    fn check_input_validity(input: &String) {
+    fn check_input_validity(input: &str) {
         for c in input.chars() {
             if !c.is_ascii_hexdigit() {
                 panic!();
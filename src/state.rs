@@ -0,0 +1,398 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs, mem,
+    path::Path,
+};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    storage::Storage,
+    utils::{address::Address, bytes32::Bytes32, crypto::keccak256, errors::StateError},
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Account {
+    pub balance: u128,
+    pub nonce: u64,
+    #[serde(deserialize_with = "deserialize_hex")]
+    pub code: Vec<u8>,
+    pub storage: Storage,
+}
+
+impl Account {
+    pub fn new(balance: u128) -> Self {
+        Self {
+            balance,
+            ..Default::default()
+        }
+    }
+
+    /// An account is empty when it has no code, zero nonce and zero balance (EIP-161).
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty() && self.nonce == 0 && self.balance == 0
+    }
+
+    pub fn code_hash(&self) -> Bytes32 {
+        keccak256(&self.code)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct WorldState {
+    accounts: HashMap<Address, Account>,
+    #[serde(skip)]
+    warm_addresses: HashSet<Address>,
+    #[serde(skip)]
+    warm_slots: HashSet<(Address, Bytes32)>,
+    #[serde(skip)]
+    original_slots: HashMap<(Address, Bytes32), Bytes32>,
+    #[serde(skip)]
+    created: HashSet<Address>,
+    #[serde(skip)]
+    destructed: HashSet<Address>,
+}
+
+impl WorldState {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    /// Loads a pre-state JSON file which maps addresses to accounts.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn create_account(
+        &mut self,
+        address: Address,
+        account: Account,
+    ) -> Result<&mut Account, StateError> {
+        if self.exists(&address) {
+            return Err(StateError::AccountAlreadyExists);
+        }
+
+        Ok(self.accounts.entry(address).or_insert(account))
+    }
+
+    pub fn exists(&self, address: &Address) -> bool {
+        self.accounts.contains_key(address)
+    }
+
+    /// Returns true when the account does not exist or is empty.
+    pub fn is_empty(&self, address: &Address) -> bool {
+        self.accounts
+            .get(address)
+            .is_none_or(|account| account.is_empty())
+    }
+
+    pub fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    /// Returns the account at the given address, creating an empty one if it does not exist.
+    pub fn account_mut(&mut self, address: Address) -> &mut Account {
+        self.accounts.entry(address).or_default()
+    }
+
+    pub fn remove_account(&mut self, address: &Address) -> Option<Account> {
+        self.accounts.remove(address)
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.accounts.iter()
+    }
+
+    pub fn balance(&self, address: &Address) -> u128 {
+        self.accounts
+            .get(address)
+            .map_or(0, |account| account.balance)
+    }
+
+    pub fn nonce(&self, address: &Address) -> u64 {
+        self.accounts
+            .get(address)
+            .map_or(0, |account| account.nonce)
+    }
+
+    pub fn code(&self, address: &Address) -> &[u8] {
+        self.accounts
+            .get(address)
+            .map_or(&[], |account| account.code.as_slice())
+    }
+
+    /// Returns true if the account has code, nonce or storage, so that nothing can be created at it.
+    pub fn has_collision(&self, address: &Address) -> bool {
+        self.account(address).is_some_and(|account| {
+            account.nonce != 0 || !account.code.is_empty() || !account.storage.is_empty()
+        })
+    }
+
+    /// Returns the code hash of the account, or zero when it does not exist or is empty (EIP-1052).
+    pub fn code_hash(&self, address: &Address) -> Bytes32 {
+        match self.accounts.get(address) {
+            Some(account) if !account.is_empty() => account.code_hash(),
+            _ => Bytes32::from(0),
+        }
+    }
+
+    pub fn sload(&self, address: &Address, slot: Bytes32) -> Bytes32 {
+        self.accounts
+            .get(address)
+            .and_then(|account| account.storage.sload(slot))
+            .copied()
+            .unwrap_or(Bytes32::from(0))
+    }
+
+    pub fn sstore(&mut self, address: Address, slot: Bytes32, value: Bytes32) {
+        self.account_mut(address).storage.sstore(slot, value);
+    }
+
+    /// Marks the address as accessed and returns true if it was cold (EIP-2929).
+    pub fn warm_address(&mut self, address: Address) -> bool {
+        self.warm_addresses.insert(address)
+    }
+
+    /// Marks the storage slot as accessed and returns true if it was cold.
+    pub fn warm_slot(&mut self, address: Address, slot: Bytes32) -> bool {
+        self.warm_slots.insert((address, slot))
+    }
+
+    pub fn is_warm_slot(&self, address: &Address, slot: Bytes32) -> bool {
+        self.warm_slots.contains(&(*address, slot))
+    }
+
+    /// Forgets the warm addresses and storage slots before a new transaction starts.
+    pub fn clear_warm(&mut self) {
+        self.warm_addresses.clear();
+        self.warm_slots.clear();
+    }
+
+    /// Returns the value of the slot at the start of the transaction. The first write
+    /// to a slot records its current value as the original one.
+    pub fn original_slot(&mut self, address: Address, slot: Bytes32, current: Bytes32) -> Bytes32 {
+        *self
+            .original_slots
+            .entry((address, slot))
+            .or_insert(current)
+    }
+
+    /// Forgets the original slot values before a new transaction starts.
+    pub fn clear_original_slots(&mut self) {
+        self.original_slots.clear();
+    }
+
+    pub fn is_warm(&self, address: &Address) -> bool {
+        self.warm_addresses.contains(address)
+    }
+
+    pub fn warm_addresses(&self) -> impl Iterator<Item = &Address> {
+        self.warm_addresses.iter()
+    }
+
+    pub fn warm_slots(&self) -> impl Iterator<Item = &(Address, Bytes32)> {
+        self.warm_slots.iter()
+    }
+
+    /// Records a contract created in the current transaction.
+    pub fn mark_created(&mut self, address: Address) {
+        self.created.insert(address);
+    }
+
+    pub fn is_created(&self, address: &Address) -> bool {
+        self.created.contains(address)
+    }
+
+    /// Schedules the account for deletion and returns true if it was not scheduled yet.
+    pub fn mark_destructed(&mut self, address: Address) -> bool {
+        self.destructed.insert(address)
+    }
+
+    pub fn is_destructed(&self, address: &Address) -> bool {
+        self.destructed.contains(address)
+    }
+
+    /// Deletes the self-destructed accounts at the end of a transaction.
+    pub fn finalize(&mut self) {
+        for address in mem::take(&mut self.destructed) {
+            self.accounts.remove(&address);
+        }
+
+        self.created.clear();
+    }
+
+    pub fn transfer(&mut self, from: Address, to: Address, value: u128) -> Result<(), StateError> {
+        if self.balance(&from) < value {
+            return Err(StateError::InsufficientBalance);
+        }
+
+        self.account_mut(from).balance -= value;
+        self.account_mut(to).balance += value;
+
+        Ok(())
+    }
+}
+
+pub(crate) fn deserialize_hex<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<u8>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+
+    hex::decode(s.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_creates_account() -> Result<(), StateError> {
+        let mut state = WorldState::new();
+        let address = Address::from(0x1000);
+
+        assert!(!state.exists(&address));
+        assert!(state.is_empty(&address));
+
+        state.create_account(address, Account::new(100))?;
+
+        assert!(state.exists(&address));
+        assert!(!state.is_empty(&address));
+        assert_eq!(state.balance(&address), 100);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_existing_account_returns_state_error() -> Result<(), StateError> {
+        let mut state = WorldState::new();
+        let address = Address::from(0x1000);
+
+        state.create_account(address, Account::default())?;
+        let result = state.create_account(address, Account::default());
+
+        assert!(matches!(result, Err(StateError::AccountAlreadyExists)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_distinguishes_empty_and_non_existent_accounts() {
+        let mut state = WorldState::new();
+        let address = Address::from(0x1000);
+
+        state.account_mut(address);
+
+        assert!(state.exists(&address));
+        assert!(state.is_empty(&address));
+        assert_eq!(
+            state.account(&address).unwrap().code_hash().to_string(),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn it_returns_zero_code_hash_for_empty_accounts() {
+        let mut state = WorldState::new();
+        let address = Address::from(0x1000);
+
+        assert_eq!(state.code_hash(&address), Bytes32::from(0));
+
+        state.account_mut(address);
+        assert_eq!(state.code_hash(&address), Bytes32::from(0));
+
+        state.account_mut(address).balance = 1;
+        assert_eq!(state.code_hash(&address), keccak256(&[]));
+    }
+
+    #[test]
+    fn it_stores_and_loads_per_account_storage() {
+        let mut state = WorldState::new();
+        let alice = Address::from(0xa);
+        let bob = Address::from(0xb);
+
+        state.sstore(alice, Bytes32::from(1), Bytes32::from(2));
+
+        assert_eq!(state.sload(&alice, Bytes32::from(1)), Bytes32::from(2));
+        assert_eq!(state.sload(&bob, Bytes32::from(1)), Bytes32::from(0));
+    }
+
+    #[test]
+    fn it_transfers_balance() -> Result<(), StateError> {
+        let mut state = WorldState::new();
+        let alice = Address::from(0xa);
+        let bob = Address::from(0xb);
+
+        state.create_account(alice, Account::new(100))?;
+        state.transfer(alice, bob, 40)?;
+
+        assert_eq!(state.balance(&alice), 60);
+        assert_eq!(state.balance(&bob), 40);
+
+        let result = state.transfer(alice, bob, 61);
+
+        assert!(matches!(result, Err(StateError::InsufficientBalance)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_warms_addresses() {
+        let mut state = WorldState::new();
+        let address = Address::from(0xa);
+
+        assert!(!state.is_warm(&address));
+        assert!(state.warm_address(address));
+        assert!(!state.warm_address(address));
+        assert!(state.is_warm(&address));
+    }
+
+    #[test]
+    fn it_deletes_destructed_accounts_on_finalize() {
+        let mut state = WorldState::new();
+        let address = Address::from(0xa);
+
+        state.account_mut(address).balance = 1;
+        state.mark_created(address);
+
+        assert!(state.mark_destructed(address));
+        assert!(!state.mark_destructed(address));
+
+        state.finalize();
+
+        assert!(!state.exists(&address));
+        assert!(!state.is_created(&address));
+        assert!(!state.is_destructed(&address));
+    }
+
+    #[test]
+    fn it_parses_pre_state() -> Result<(), Box<dyn Error>> {
+        let state: WorldState = serde_json::from_str(
+            r#"{
+                "0x0000000000000000000000000000000000001000": {
+                    "balance": 1000000000000000000000,
+                    "nonce": 1,
+                    "code": "0x6001",
+                    "storage": { "0x01": "0x02" }
+                },
+                "0x0000000000000000000000000000000000002000": {}
+            }"#,
+        )?;
+
+        let address = Address::from(0x1000);
+
+        assert_eq!(state.balance(&address), 1_000_000_000_000_000_000_000);
+        assert_eq!(state.nonce(&address), 1);
+        assert_eq!(state.code(&address), &[0x60, 0x01]);
+        assert_eq!(state.sload(&address, Bytes32::from(1)), Bytes32::from(2));
+        assert!(state.exists(&Address::from(0x2000)));
+
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
+use serde::Deserialize;
+
 use crate::utils::bytes32::Bytes32;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(transparent)]
 pub struct Storage {
     storage: HashMap<Bytes32, Bytes32>,
 }
@@ -32,6 +35,8 @@ impl Storage {
 }
 
 #[cfg(test)]
+// NOTE: the original tests compare against bool literals
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use std::error::Error;
 
@@ -0,0 +1,1260 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, fs, mem, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    env::{BlockEnv, TxEnv, DEFAULT_CALLER},
+    gas::{self, Gas, DEFAULT_GAS_LIMIT},
+    precompile,
+    spec::SpecId,
+    state::{deserialize_hex, WorldState},
+    utils::{
+        address::Address,
+        bytes32::Bytes32,
+        crypto,
+        errors::{RlpError, TransactionError},
+        rlp::RlpItem,
+    },
+    vm::{self, ExecutionResult, Log, Vm},
+};
+
+/// An account and its storage keys which are warm from the start (EIP-2930).
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    #[serde(default, alias = "storage_keys")]
+    pub storage_keys: Vec<Bytes32>,
+}
+
+pub type AccessList = Vec<AccessListItem>;
+
+/// A transaction as sent by an externally owned account. Without a recipient
+/// the data is run as init code and the returned code is deployed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Transaction {
+    pub caller: Address,
+    pub to: Option<Address>,
+    pub value: u128,
+    #[serde(deserialize_with = "deserialize_hex")]
+    pub data: Vec<u8>,
+    /// Checked against the sender nonce when given.
+    pub nonce: Option<u64>,
+    pub gas_limit: u64,
+    /// Checked against the block chain id when given.
+    pub chain_id: Option<u64>,
+    pub gas_price: Option<u128>,
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+    #[serde(alias = "accessList")]
+    pub access_list: AccessList,
+}
+
+impl Default for Transaction {
+    fn default() -> Self {
+        Self {
+            caller: Address::from(DEFAULT_CALLER),
+            to: None,
+            value: 0,
+            data: vec![],
+            nonce: None,
+            gas_limit: DEFAULT_GAS_LIMIT,
+            chain_id: None,
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            access_list: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TransactionResult {
+    /// Gas paid by the sender, including the intrinsic gas and after the refund.
+    pub gas_used: u64,
+    pub gas_refunded: u64,
+    pub intrinsic_gas: u64,
+    pub effective_gas_price: u128,
+    pub contract_address: Option<Address>,
+    pub output: Vec<u8>,
+    pub logs: Vec<Log>,
+    pub reverted: bool,
+    /// Every address and storage slot warm by the end of the execution.
+    pub accessed: AccessList,
+}
+
+/// An access list generated by running a transaction, along with the gas used
+/// with and without it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListResult {
+    pub access_list: AccessList,
+    pub gas_used: u64,
+    pub gas_used_without_access_list: u64,
+}
+
+impl Transaction {
+    /// Loads a transaction from a JSON file, e.g. `{ "to": "0x...", "data": "0x...",
+    /// "access_list": [{ "address": "0x...", "storageKeys": ["0x01"] }] }`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn is_create(&self) -> bool {
+        self.to.is_none()
+    }
+
+    pub fn intrinsic_gas(&self, spec: SpecId) -> u64 {
+        let storage_keys = self
+            .access_list
+            .iter()
+            .map(|item| item.storage_keys.len() as u64)
+            .sum();
+
+        gas::intrinsic_cost(&self.data, self.is_create(), spec)
+            + gas::access_list_cost(self.access_list.len() as u64, storage_keys)
+    }
+
+    pub fn is_dynamic_fee(&self) -> bool {
+        self.max_fee_per_gas.is_some() || self.max_priority_fee_per_gas.is_some()
+    }
+
+    /// Returns the max fee and the max priority fee per gas. A legacy gas price is
+    /// used for both, so that it is paid in full (EIP-1559).
+    pub fn fees(&self) -> (u128, u128) {
+        match (self.max_fee_per_gas, self.gas_price) {
+            (Some(max_fee), _) => (max_fee, self.max_priority_fee_per_gas.unwrap_or(0)),
+            (None, Some(gas_price)) => (gas_price, gas_price),
+            (None, None) => (0, 0),
+        }
+    }
+
+    /// Returns the price paid per gas, which is capped by the base fee plus the priority fee since London.
+    pub fn effective_gas_price(&self, basefee: u128, spec: SpecId) -> u128 {
+        let (max_fee, priority_fee) = self.fees();
+
+        if spec.is_enabled_in(SpecId::London) {
+            max_fee.min(basefee.saturating_add(priority_fee))
+        } else {
+            max_fee
+        }
+    }
+
+    /// Checks the transaction against the sender account and the block before anything is charged.
+    pub fn validate(
+        &self,
+        state: &WorldState,
+        block: &BlockEnv,
+        spec: SpecId,
+    ) -> Result<(), TransactionError> {
+        if self.is_dynamic_fee() {
+            if !spec.is_enabled_in(SpecId::London) {
+                return Err(TransactionError::UnsupportedDynamicFee);
+            }
+
+            if self.gas_price.is_some() {
+                return Err(TransactionError::ConflictingFees);
+            }
+        }
+
+        let (max_fee, priority_fee) = self.fees();
+
+        if spec.is_enabled_in(SpecId::London) {
+            if priority_fee > max_fee {
+                return Err(TransactionError::PriorityFeeTooHigh);
+            }
+
+            if max_fee < block.basefee {
+                return Err(TransactionError::FeeCapTooLow);
+            }
+        }
+
+        if !self.access_list.is_empty() && !spec.is_enabled_in(SpecId::Berlin) {
+            return Err(TransactionError::UnsupportedAccessList);
+        }
+
+        // NOTE: CREATE and CREATE2 check the same limit in `Vm::create` (EIP-3860)
+        if self.is_create()
+            && spec.is_enabled_in(SpecId::Shanghai)
+            && self.data.len() > vm::MAX_INITCODE_SIZE
+        {
+            return Err(TransactionError::InitCodeSizeLimit);
+        }
+
+        if let Some(chain_id) = self.chain_id {
+            if chain_id != block.chain_id {
+                return Err(TransactionError::InvalidChainId(block.chain_id, chain_id));
+            }
+        }
+
+        let nonce = state.nonce(&self.caller);
+        if let Some(given) = self.nonce {
+            if given != nonce {
+                return Err(TransactionError::InvalidNonce(nonce, given));
+            }
+        }
+
+        if self.gas_limit > block.gas_limit {
+            return Err(TransactionError::GasLimitExceedsBlock);
+        }
+
+        if self.gas_limit < self.intrinsic_gas(spec) {
+            return Err(TransactionError::IntrinsicGasTooLow);
+        }
+
+        let cost = (self.gas_limit as u128)
+            .checked_mul(max_fee)
+            .and_then(|fee| fee.checked_add(self.value))
+            .ok_or(TransactionError::InsufficientFunds)?;
+
+        if state.balance(&self.caller) < cost {
+            return Err(TransactionError::InsufficientFunds);
+        }
+
+        Ok(())
+    }
+
+    /// Validates and executes the transaction against the state. The sender pays the
+    /// gas up front and gets the unused gas and the refund back, the coinbase is paid
+    /// the priority fee. A reverted execution keeps the nonce and the fee payment.
+    pub fn execute(
+        &self,
+        state: &mut WorldState,
+        block: &BlockEnv,
+        spec: SpecId,
+    ) -> Result<TransactionResult, Box<dyn Error>> {
+        self.validate(state, block, spec)?;
+
+        let intrinsic_gas = self.intrinsic_gas(spec);
+        let gas_price = self.effective_gas_price(block.basefee, spec);
+
+        let nonce = state.nonce(&self.caller);
+        let sender = state.account_mut(self.caller);
+        sender.balance -= self.gas_limit as u128 * gas_price;
+        sender.nonce += 1;
+
+        state.clear_warm();
+        state.clear_original_slots();
+
+        for item in &self.access_list {
+            state.warm_address(item.address);
+
+            for key in &item.storage_keys {
+                state.warm_slot(item.address, *key);
+            }
+        }
+
+        let address = match self.to {
+            Some(to) => to,
+            None => crypto::create_address(self.caller, nonce),
+        };
+        let gas_limit = self.gas_limit - intrinsic_gas;
+
+        let snapshot = state.clone();
+        let (mut result, refunded) = if self.is_create() && state.has_collision(&address) {
+            (
+                ExecutionResult {
+                    gas_used: gas_limit,
+                    reverted: true,
+                    ..Default::default()
+                },
+                0,
+            )
+        } else {
+            self.run(state, block, spec, address, gas_limit)?
+        };
+
+        if self.is_create() {
+            vm::deposit_code(state, spec, address, &mut result, gas_limit);
+        }
+
+        let accessed = accessed(state);
+
+        if result.reverted {
+            *state = snapshot;
+        }
+
+        let gas_used = intrinsic_gas + result.gas_used;
+        let gas_refunded = if result.reverted {
+            0
+        } else {
+            gas::capped_refund(spec, gas_used, refunded)
+        };
+        let gas_used = gas_used - gas_refunded;
+
+        state.account_mut(self.caller).balance += (self.gas_limit - gas_used) as u128 * gas_price;
+
+        // NOTE: the base fee is burnt since London (EIP-1559)
+        let coinbase_price = if spec.is_enabled_in(SpecId::London) {
+            gas_price - block.basefee
+        } else {
+            gas_price
+        };
+        state.account_mut(block.coinbase).balance += gas_used as u128 * coinbase_price;
+
+        Ok(TransactionResult {
+            gas_used,
+            gas_refunded,
+            intrinsic_gas,
+            effective_gas_price: gas_price,
+            contract_address: if self.is_create() && !result.reverted {
+                Some(address)
+            } else {
+                None
+            },
+            output: result.output,
+            logs: result.logs,
+            reverted: result.reverted,
+            accessed,
+        })
+    }
+
+    /// Runs the transaction without an access list and builds one from the addresses
+    /// and storage slots it touched. The sender, the recipient and the precompiles are
+    /// left out, and so is any other entry which costs more gas than it saves. The list
+    /// is left empty when it does not make the transaction cheaper. Both runs start
+    /// from a copy of the state.
+    pub fn create_access_list(
+        &self,
+        state: &WorldState,
+        block: &BlockEnv,
+        spec: SpecId,
+    ) -> Result<AccessListResult, Box<dyn Error>> {
+        if !spec.is_enabled_in(SpecId::Berlin) {
+            return Err(Box::new(TransactionError::UnsupportedAccessList));
+        }
+
+        let mut tx = Transaction {
+            access_list: vec![],
+            ..self.clone()
+        };
+        let without = tx.execute(&mut state.clone(), block, spec)?;
+
+        let recipient = match self.to {
+            Some(to) => to,
+            None => crypto::create_address(self.caller, state.nonce(&self.caller)),
+        };
+        let precompiles: Vec<Address> = precompile::addresses(spec).collect();
+
+        // NOTE: the coinbase is warm from the start since Shanghai (EIP-3651)
+        let is_excluded = |address: &Address| {
+            *address == self.caller
+                || *address == recipient
+                || precompiles.contains(address)
+                || (*address == block.coinbase && spec.is_enabled_in(SpecId::Shanghai))
+        };
+
+        // NOTE: a listed address saves the cold account surcharge, and each listed
+        // slot saves the cold load surcharge
+        let saving = |item: &AccessListItem| {
+            let keys = item.storage_keys.len() as u64;
+            let keys_saving = keys * (gas::COLD_SLOAD_GAS - gas::WARM_STORAGE_READ_GAS);
+            let address_saving = gas::COLD_ACCOUNT_ACCESS_GAS - gas::WARM_ACCOUNT_ACCESS_GAS;
+
+            (address_saving + keys_saving) as i64 - gas::access_list_cost(1, keys) as i64
+        };
+
+        tx.access_list = without
+            .accessed
+            .iter()
+            .filter(|item| !is_excluded(&item.address))
+            .filter(|item| saving(item) > 0)
+            .cloned()
+            .collect();
+        let mut gas_used = tx.execute(&mut state.clone(), block, spec)?.gas_used;
+
+        // NOTE: an address may be warm without having paid for it, e.g. a created one
+        if gas_used >= without.gas_used {
+            tx.access_list = vec![];
+            gas_used = without.gas_used;
+        }
+
+        Ok(AccessListResult {
+            access_list: tx.access_list,
+            gas_used,
+            gas_used_without_access_list: without.gas_used,
+        })
+    }
+
+    /// Decodes a signed raw transaction and recovers its sender. Legacy transactions are
+    /// a bare RLP list, typed ones are prefixed with 0x01 for access lists (EIP-2930) or
+    /// 0x02 for dynamic fees (EIP-1559), followed by the RLP list (EIP-2718).
+    pub fn decode(raw: &[u8]) -> Result<Transaction, Box<dyn Error>> {
+        let tx_type = *raw.first().ok_or(RlpError::UnexpectedEnd)?;
+
+        // NOTE: a legacy transaction starts with a list prefix, which is at least 0xc0
+        if tx_type >= 0xc0 {
+            return decode_legacy(raw);
+        }
+
+        let item = RlpItem::decode(&raw[1..])?;
+        let fields = item.as_list()?;
+
+        let (fields, signature) = match (tx_type, fields.len()) {
+            (0x01, 11) | (0x02, 12) => fields.split_at(fields.len() - 3),
+            (0x01 | 0x02, _) => return Err(Box::new(TransactionError::InvalidField)),
+            _ => return Err(Box::new(TransactionError::UnsupportedType(tx_type))),
+        };
+
+        let mut payload = vec![tx_type];
+        payload.extend(RlpItem::List(fields.to_vec()).encode());
+
+        let y_parity = signature[0].as_u64()?;
+        let recovery_id = u8::try_from(y_parity).map_err(|_| TransactionError::InvalidSignature)?;
+        let caller = recover_sender(&payload, &signature[1], &signature[2], recovery_id)?;
+
+        // NOTE: dynamic fee transactions replace the gas price with two fields
+        let (gas_price, max_priority_fee_per_gas, max_fee_per_gas, rest) = if tx_type == 0x01 {
+            (Some(fields[2].as_u128()?), None, None, &fields[3..])
+        } else {
+            let priority_fee = fields[2].as_u128()?;
+            (
+                None,
+                Some(priority_fee),
+                Some(fields[3].as_u128()?),
+                &fields[4..],
+            )
+        };
+
+        Ok(Transaction {
+            caller,
+            to: decode_to(&rest[1])?,
+            value: rest[2].as_u128()?,
+            data: rest[3].as_bytes()?.to_vec(),
+            nonce: Some(fields[1].as_u64()?),
+            gas_limit: rest[0].as_u64()?,
+            chain_id: Some(fields[0].as_u64()?),
+            gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            access_list: decode_access_list(&rest[4])?,
+        })
+    }
+
+    /// Runs the top-level frame and returns its result along with the uncapped refund.
+    /// An exceptional halt consumes all of the gas, any other error is returned.
+    fn run(
+        &self,
+        state: &mut WorldState,
+        block: &BlockEnv,
+        spec: SpecId,
+        address: Address,
+        gas_limit: u64,
+    ) -> Result<(ExecutionResult, i64), Box<dyn Error>> {
+        if self.is_create() {
+            if spec.is_enabled_in(SpecId::SpuriousDragon) {
+                state.account_mut(address).nonce = 1;
+            }
+            state.mark_created(address);
+        }
+
+        state.transfer(self.caller, address, self.value)?;
+
+        if let Some(precompile) = precompile::get(&address, spec) {
+            return Ok((vm::run_precompile(precompile, &self.data, gas_limit), 0));
+        }
+
+        let (code, calldata) = if self.is_create() {
+            (self.data.clone(), vec![])
+        } else {
+            (state.code(&address).to_vec(), self.data.clone())
+        };
+
+        let bytecode = hex::encode(code);
+        let mut vm = Vm::new(&bytecode, false)?;
+        vm.gas = Gas::new(gas_limit);
+        vm.calldata = calldata;
+        vm.block = block.clone();
+        vm.tx = TxEnv {
+            address,
+            origin: self.caller,
+            caller: self.caller,
+            value: self.value,
+            gas_price: self.effective_gas_price(block.basefee, spec),
+        };
+        vm.spec = spec;
+        vm.state = mem::take(state);
+
+        let result = vm.run();
+
+        *state = mem::take(&mut vm.state);
+
+        Ok(match result {
+            Ok(result) => (result, vm.gas.refunded()),
+            Err(error) if !vm::is_exceptional_halt(error.as_ref()) => return Err(error),
+            Err(_) => (
+                ExecutionResult {
+                    gas_used: gas_limit,
+                    reverted: true,
+                    ..Default::default()
+                },
+                0,
+            ),
+        })
+    }
+}
+
+/// Decodes a legacy transaction, whose v carries the chain id since EIP-155 as
+/// chain_id * 2 + 35 + recovery id, or is 27 + recovery id without one.
+fn decode_legacy(raw: &[u8]) -> Result<Transaction, Box<dyn Error>> {
+    let item = RlpItem::decode(raw)?;
+    let fields = item.as_list()?;
+
+    if fields.len() != 9 {
+        return Err(Box::new(TransactionError::InvalidField));
+    }
+
+    let v = fields[6].as_u64()?;
+    let (chain_id, recovery_id) = match v {
+        27 | 28 => (None, v - 27),
+        35.. => (Some((v - 35) / 2), (v - 35) % 2),
+        _ => return Err(Box::new(TransactionError::InvalidSignature)),
+    };
+
+    let mut unsigned = fields[..6].to_vec();
+    if let Some(chain_id) = chain_id {
+        unsigned.extend([
+            RlpItem::from(chain_id),
+            RlpItem::from(0u64),
+            RlpItem::from(0u64),
+        ]);
+    }
+
+    let payload = RlpItem::List(unsigned).encode();
+    let caller = recover_sender(&payload, &fields[7], &fields[8], recovery_id as u8)?;
+
+    Ok(Transaction {
+        caller,
+        to: decode_to(&fields[3])?,
+        value: fields[4].as_u128()?,
+        data: fields[5].as_bytes()?.to_vec(),
+        nonce: Some(fields[0].as_u64()?),
+        gas_limit: fields[2].as_u64()?,
+        chain_id,
+        gas_price: Some(fields[1].as_u128()?),
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        access_list: vec![],
+    })
+}
+
+/// Recovers the address which signed the keccak256 hash of the payload with r and s.
+/// Signatures with a high s are rejected as malleable (EIP-2).
+fn recover_sender(
+    payload: &[u8],
+    r: &RlpItem,
+    s: &RlpItem,
+    recovery_id: u8,
+) -> Result<Address, Box<dyn Error>> {
+    let mut signature = [0u8; 64];
+
+    for (scalar, chunk) in [r, s].into_iter().zip(signature.chunks_mut(32)) {
+        let bytes = scalar.as_bytes()?;
+
+        if bytes.len() > 32 {
+            return Err(Box::new(TransactionError::InvalidSignature));
+        }
+
+        chunk[32 - bytes.len()..].copy_from_slice(bytes);
+    }
+
+    if !crypto::is_low_s(&signature) {
+        return Err(Box::new(TransactionError::InvalidSignature));
+    }
+
+    let public_key =
+        crypto::recover_public_key(&crypto::keccak256(payload), &signature, recovery_id)
+            .ok_or(TransactionError::InvalidSignature)?;
+
+    Ok(crypto::public_key_to_address(&public_key))
+}
+
+/// An empty recipient creates a contract.
+fn decode_to(item: &RlpItem) -> Result<Option<Address>, Box<dyn Error>> {
+    match item.as_bytes()? {
+        [] => Ok(None),
+        bytes => Ok(Some(Address(
+            bytes
+                .try_into()
+                .map_err(|_| TransactionError::InvalidField)?,
+        ))),
+    }
+}
+
+fn decode_access_list(item: &RlpItem) -> Result<AccessList, Box<dyn Error>> {
+    item.as_list()?
+        .iter()
+        .map(|entry| {
+            let [address, storage_keys] = entry.as_list()? else {
+                return Err(Box::new(TransactionError::InvalidField) as Box<dyn Error>);
+            };
+
+            let storage_keys = storage_keys
+                .as_list()?
+                .iter()
+                .map(|key| {
+                    let key = key.as_bytes()?;
+                    let key = key.try_into().map_err(|_| TransactionError::InvalidField)?;
+
+                    Ok(Bytes32(key))
+                })
+                .collect::<Result<_, Box<dyn Error>>>()?;
+
+            Ok(AccessListItem {
+                address: decode_to(address)?.ok_or(TransactionError::InvalidField)?,
+                storage_keys,
+            })
+        })
+        .collect()
+}
+
+/// Collects the warm addresses and storage slots of the state, sorted by address and key.
+// NOTE: `History` only records the slots written by SSTORE, without their address, so
+// loads, account accesses and the slots of nested calls are taken from the warm sets
+fn accessed(state: &WorldState) -> AccessList {
+    let mut slots: BTreeMap<Address, Vec<Bytes32>> = state
+        .warm_addresses()
+        .map(|address| (*address, vec![]))
+        .collect();
+
+    for (address, key) in state.warm_slots() {
+        slots.entry(*address).or_default().push(*key);
+    }
+
+    slots
+        .into_iter()
+        .map(|(address, mut storage_keys)| {
+            storage_keys.sort_by_key(|key| key.0);
+            AccessListItem {
+                address,
+                storage_keys,
+            }
+        })
+        .collect()
+}
+
+impl Display for TransactionResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = if self.reverted { "reverted" } else { "success" };
+
+        writeln!(f, "Status: {status}")?;
+        writeln!(
+            f,
+            "Gas used: {} (intrinsic: {}, refunded: {})",
+            self.gas_used, self.intrinsic_gas, self.gas_refunded
+        )?;
+        writeln!(f, "Effective gas price: {}", self.effective_gas_price)?;
+
+        if let Some(address) = self.contract_address {
+            writeln!(f, "Contract address: {address}")?;
+        }
+
+        write!(f, "Output: 0x{}", hex::encode(&self.output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::Account;
+
+    use super::*;
+
+    const BALANCE: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn it_executes_value_transfer() -> Result<(), Box<dyn Error>> {
+        let (mut state, block) = create_env();
+        let tx = Transaction {
+            to: Some(Address::from(0xbeef)),
+            value: 100,
+            gas_price: Some(10),
+            gas_limit: 50_000,
+            ..Default::default()
+        };
+
+        let result = tx.execute(&mut state, &block, SpecId::Cancun)?;
+
+        assert_eq!(result.gas_used, 21_000);
+        assert!(!result.reverted);
+        assert_eq!(state.balance(&Address::from(0xbeef)), 100);
+        assert_eq!(state.balance(&tx.caller), BALANCE - 100 - 210_000);
+        assert_eq!(state.balance(&block.coinbase), 210_000);
+        assert_eq!(state.nonce(&tx.caller), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_executes_contract_call() -> Result<(), Box<dyn Error>> {
+        let (mut state, block) = create_env();
+        state.account_mut(Address::from(0xbeef)).code = hex::decode("602a600055")?;
+
+        let tx = Transaction {
+            to: Some(Address::from(0xbeef)),
+            data: vec![0, 1],
+            ..Default::default()
+        };
+
+        let result = tx.execute(&mut state, &block, SpecId::Cancun)?;
+
+        // NOTE: intrinsic (21000 + 4 + 16) + 2 pushes (6) + SSTORE (20000) + cold slot (2100)
+        assert_eq!(result.intrinsic_gas, 21_020);
+        assert_eq!(result.gas_used, 43_126);
+        assert_eq!(
+            state.sload(&Address::from(0xbeef), Bytes32::from(0)),
+            Bytes32::from(0x2a)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_executes_create_transaction() -> Result<(), Box<dyn Error>> {
+        let (mut state, block) = create_env();
+
+        // NOTE: the init code returns the runtime code which returns 42
+        let runtime_code = "602a60005260206000f3";
+        let tx = Transaction {
+            data: hex::decode(format!("69{runtime_code}600052600a6016f3"))?,
+            ..Default::default()
+        };
+
+        let result = tx.execute(&mut state, &block, SpecId::Cancun)?;
+        let address = crypto::create_address(tx.caller, 0);
+
+        assert_eq!(result.contract_address, Some(address));
+        assert_eq!(state.code(&address), hex::decode(runtime_code)?);
+        assert_eq!(state.nonce(&address), 1);
+        assert_eq!(state.nonce(&tx.caller), 1);
+        // NOTE: intrinsic (21000 + 16 * 16 + 3 * 4 + 32000 + 2) + init code (18) + deposit (2000)
+        assert_eq!(result.gas_used, 55_288);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_nonce_and_fee_on_revert() -> Result<(), Box<dyn Error>> {
+        let (mut state, block) = create_env();
+        state.account_mut(Address::from(0xbeef)).code = hex::decode("60006000fd")?;
+
+        let tx = Transaction {
+            to: Some(Address::from(0xbeef)),
+            value: 100,
+            gas_price: Some(1),
+            ..Default::default()
+        };
+
+        let result = tx.execute(&mut state, &block, SpecId::Cancun)?;
+
+        assert!(result.reverted);
+        assert_eq!(result.gas_used, 21_006);
+        assert_eq!(state.balance(&Address::from(0xbeef)), 0);
+        assert_eq!(state.balance(&tx.caller), BALANCE - 21_006);
+        assert_eq!(state.nonce(&tx.caller), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_pays_priority_fee_to_coinbase() -> Result<(), Box<dyn Error>> {
+        let (mut state, mut block) = create_env();
+        block.basefee = 10;
+
+        let tx = Transaction {
+            to: Some(Address::from(0xbeef)),
+            max_fee_per_gas: Some(30),
+            max_priority_fee_per_gas: Some(5),
+            ..Default::default()
+        };
+
+        let result = tx.execute(&mut state, &block, SpecId::Cancun)?;
+
+        assert_eq!(result.effective_gas_price, 15);
+        assert_eq!(state.balance(&tx.caller), BALANCE - 21_000 * 15);
+        assert_eq!(state.balance(&block.coinbase), 21_000 * 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_applies_capped_refund() -> Result<(), Box<dyn Error>> {
+        let (mut state, block) = create_env();
+        state.account_mut(Address::from(0xbeef)).code = hex::decode("6000ff")?;
+
+        let tx = Transaction {
+            to: Some(Address::from(0xbeef)),
+            ..Default::default()
+        };
+
+        let result = tx.execute(&mut state, &block, SpecId::Berlin)?;
+
+        // NOTE: intrinsic (21000) + PUSH1 (3) + SELFDESTRUCT (5000) + cold beneficiary (2500),
+        // half of which is refunded before London
+        assert_eq!(result.gas_refunded, 14_251);
+        assert_eq!(result.gas_used, 14_252);
+        assert!(!state.exists(&Address::from(0xbeef)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_prewarms_access_list() -> Result<(), Box<dyn Error>> {
+        // NOTE: loads slot 1 of the contract and the balance of 0xcafe
+        let code = hex::decode("60015461cafe31")?;
+        let call = |access_list: AccessList| -> Result<TransactionResult, Box<dyn Error>> {
+            let (mut state, block) = create_env();
+            state.account_mut(Address::from(0xbeef)).code = code.clone();
+
+            let tx = Transaction {
+                to: Some(Address::from(0xbeef)),
+                access_list,
+                ..Default::default()
+            };
+
+            tx.execute(&mut state, &block, SpecId::Cancun)
+        };
+
+        // NOTE: 2 pushes (6) + cold SLOAD (2100) + cold BALANCE (2600)
+        let result = call(vec![])?;
+        assert_eq!(result.gas_used, 25_706);
+
+        // NOTE: access list (2400 * 2 + 1900) + warm SLOAD (100) + warm BALANCE (100)
+        let result = call(vec![
+            AccessListItem {
+                address: Address::from(0xbeef),
+                storage_keys: vec![Bytes32::from(1)],
+            },
+            AccessListItem {
+                address: Address::from(0xcafe),
+                storage_keys: vec![],
+            },
+        ])?;
+        assert_eq!(result.intrinsic_gas, 27_700);
+        assert_eq!(result.gas_used, 27_906);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_creates_access_list() -> Result<(), Box<dyn Error>> {
+        // NOTE: loads slot 1 of the contract, the balance of 0xcafe and of the sender
+        let (mut state, block) = create_env();
+        state.account_mut(Address::from(0xbeef)).code = hex::decode("60015461cafe313331")?;
+
+        let tx = Transaction {
+            to: Some(Address::from(0xbeef)),
+            ..Default::default()
+        };
+        let result = tx.create_access_list(&state, &block, SpecId::Cancun)?;
+
+        // NOTE: the recipient is left out even though its slot was loaded
+        assert_eq!(
+            result.access_list,
+            vec![AccessListItem {
+                address: Address::from(0xcafe),
+                storage_keys: vec![],
+            }]
+        );
+        assert_eq!(result.gas_used_without_access_list, 25_808);
+        assert_eq!(result.gas_used, 25_708);
+
+        // NOTE: a list which does not save gas is left empty
+        state.account_mut(Address::from(0xbeef)).code = hex::decode("600154")?;
+        let result = tx.create_access_list(&state, &block, SpecId::Cancun)?;
+
+        assert_eq!(result.access_list, vec![]);
+        assert_eq!(result.gas_used, result.gas_used_without_access_list);
+        assert_eq!(state.nonce(&Address::from(DEFAULT_CALLER)), 0);
+
+        let result = tx.create_access_list(&state, &block, SpecId::Istanbul);
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_leaves_recipient_out_of_access_list() -> Result<(), Box<dyn Error>> {
+        // NOTE: loads slots 1 to 3 of the contract, which would save gas if listed
+        let (mut state, block) = create_env();
+        state.account_mut(Address::from(0xbeef)).code = hex::decode("600154600254600354")?;
+
+        let tx = Transaction {
+            to: Some(Address::from(0xbeef)),
+            ..Default::default()
+        };
+        let without = tx.execute(&mut state.clone(), &block, SpecId::Cancun)?;
+        let result = tx.create_access_list(&state, &block, SpecId::Cancun)?;
+
+        assert!(without
+            .accessed
+            .iter()
+            .any(|item| item.address == Address::from(0xbeef) && item.storage_keys.len() == 3));
+        assert!(result
+            .access_list
+            .iter()
+            .all(|item| item.address != Address::from(0xbeef)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_transactions_return_transaction_error() {
+        let (mut state, mut block) = create_env();
+        block.basefee = 10;
+
+        let cases = [
+            (
+                Transaction {
+                    nonce: Some(1),
+                    gas_price: Some(10),
+                    ..Default::default()
+                },
+                TransactionError::InvalidNonce(0, 1),
+            ),
+            (
+                Transaction {
+                    gas_price: Some(1),
+                    ..Default::default()
+                },
+                TransactionError::FeeCapTooLow,
+            ),
+            (
+                Transaction {
+                    max_fee_per_gas: Some(10),
+                    max_priority_fee_per_gas: Some(11),
+                    ..Default::default()
+                },
+                TransactionError::PriorityFeeTooHigh,
+            ),
+            (
+                Transaction {
+                    gas_limit: 21_000,
+                    gas_price: Some(10),
+                    ..Default::default()
+                },
+                TransactionError::IntrinsicGasTooLow,
+            ),
+            (
+                Transaction {
+                    gas_limit: 40_000_000,
+                    gas_price: Some(10),
+                    ..Default::default()
+                },
+                TransactionError::GasLimitExceedsBlock,
+            ),
+            (
+                Transaction {
+                    value: BALANCE,
+                    gas_price: Some(10),
+                    ..Default::default()
+                },
+                TransactionError::InsufficientFunds,
+            ),
+        ];
+
+        for (tx, error) in cases {
+            assert_eq!(tx.validate(&state, &block, SpecId::Cancun), Err(error));
+        }
+
+        let tx = Transaction {
+            gas_price: Some(10),
+            access_list: vec![AccessListItem::default()],
+            ..Default::default()
+        };
+        assert_eq!(
+            tx.validate(&state, &block, SpecId::Istanbul),
+            Err(TransactionError::UnsupportedAccessList)
+        );
+
+        let tx = Transaction {
+            max_fee_per_gas: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            tx.validate(&state, &block, SpecId::Berlin),
+            Err(TransactionError::UnsupportedDynamicFee)
+        );
+
+        let tx = Transaction {
+            gas_price: Some(10),
+            max_fee_per_gas: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(
+            tx.validate(&state, &block, SpecId::Cancun),
+            Err(TransactionError::ConflictingFees)
+        );
+
+        // NOTE: one byte more than the init code size limit
+        let tx = Transaction {
+            data: vec![0; vm::MAX_INITCODE_SIZE + 1],
+            gas_price: Some(10),
+            gas_limit: 10_000_000,
+            ..Default::default()
+        };
+        assert_eq!(tx.data.len(), 49_153);
+        assert_eq!(
+            tx.validate(&state, &block, SpecId::Cancun),
+            Err(TransactionError::InitCodeSizeLimit)
+        );
+        assert_eq!(tx.validate(&state, &block, SpecId::Merge), Ok(()));
+
+        assert!(Transaction::default()
+            .execute(&mut state, &block, SpecId::Cancun)
+            .is_err());
+        assert_eq!(state.nonce(&Address::from(DEFAULT_CALLER)), 0);
+    }
+
+    #[test]
+    fn it_parses_transaction() -> Result<(), Box<dyn Error>> {
+        let tx: Transaction = serde_json::from_str(
+            r#"{
+                "to": "0x000000000000000000000000000000000000beef",
+                "data": "0xa9059cbb",
+                "gas_limit": 100000,
+                "max_fee_per_gas": 2,
+                "accessList": [
+                    {
+                        "address": "0x000000000000000000000000000000000000cafe",
+                        "storageKeys": ["0x01"]
+                    }
+                ]
+            }"#,
+        )?;
+
+        assert_eq!(tx.to, Some(Address::from(0xbeef)));
+        assert_eq!(tx.data, vec![0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(tx.caller, Address::from(DEFAULT_CALLER));
+        assert_eq!(tx.fees(), (2, 0));
+        assert_eq!(tx.access_list[0].address, Address::from(0xcafe));
+        assert_eq!(tx.access_list[0].storage_keys, vec![Bytes32::from(1)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_decodes_legacy_transaction() -> Result<(), Box<dyn Error>> {
+        // NOTE: the EIP-155 example signed with the private key 0x4646...46
+        let raw = hex::decode(
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000\
+             8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f\
+             761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+        )?;
+        let tx = Transaction::decode(&raw)?;
+
+        assert_eq!(
+            tx.caller,
+            "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f".parse::<Address>()?
+        );
+        assert_eq!(
+            tx.to,
+            Some("0x3535353535353535353535353535353535353535".parse()?)
+        );
+        assert_eq!(tx.value, 1_000_000_000_000_000_000);
+        assert_eq!(tx.nonce, Some(9));
+        assert_eq!(tx.gas_limit, 21_000);
+        assert_eq!(tx.gas_price, Some(20_000_000_000));
+        assert_eq!(tx.chain_id, Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_decodes_typed_transactions() -> Result<(), Box<dyn Error>> {
+        let sender = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf".parse::<Address>()?;
+        let access_list = RlpItem::from(vec![RlpItem::from(vec![
+            RlpItem::from(Address::from(0xcafe)),
+            RlpItem::from(vec![RlpItem::from(Bytes32::from(1).0.to_vec())]),
+        ])]);
+
+        let raw = sign(
+            0x01,
+            vec![
+                RlpItem::from(1u64),
+                RlpItem::from(0u64),
+                RlpItem::from(7u64),
+                RlpItem::from(30_000u64),
+                RlpItem::from(Address::from(0xbeef)),
+                RlpItem::from(5u64),
+                RlpItem::from(vec![0xaa, 0xbb]),
+                access_list,
+            ],
+        )?;
+        let tx = Transaction::decode(&raw)?;
+
+        assert_eq!(tx.caller, sender);
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.gas_price, Some(7));
+        assert_eq!(tx.gas_limit, 30_000);
+        assert_eq!(tx.to, Some(Address::from(0xbeef)));
+        assert_eq!(tx.value, 5);
+        assert_eq!(tx.data, vec![0xaa, 0xbb]);
+        assert_eq!(
+            tx.access_list,
+            vec![AccessListItem {
+                address: Address::from(0xcafe),
+                storage_keys: vec![Bytes32::from(1)],
+            }]
+        );
+
+        // NOTE: a contract creation with dynamic fees
+        let raw = sign(
+            0x02,
+            vec![
+                RlpItem::from(1u64),
+                RlpItem::from(0u64),
+                RlpItem::from(2u64),
+                RlpItem::from(10u64),
+                RlpItem::from(100_000u64),
+                RlpItem::Bytes(vec![]),
+                RlpItem::from(0u64),
+                RlpItem::from(hex::decode("602a6000526001601ff3")?),
+                RlpItem::List(vec![]),
+            ],
+        )?;
+        let tx = Transaction::decode(&raw)?;
+
+        assert_eq!(tx.caller, sender);
+        assert_eq!(tx.to, None);
+        assert_eq!(tx.gas_price, None);
+        assert_eq!(tx.max_priority_fee_per_gas, Some(2));
+        assert_eq!(tx.max_fee_per_gas, Some(10));
+
+        let (mut state, block) = create_env();
+        *state.account_mut(sender) = Account::new(BALANCE);
+
+        let result = tx.execute(&mut state, &block, SpecId::Cancun)?;
+        let address = crypto::create_address(sender, 0);
+
+        assert_eq!(result.contract_address, Some(address));
+        assert_eq!(state.code(&address), [0x2a]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_raw_transactions_return_error() -> Result<(), Box<dyn Error>> {
+        let error = |raw: &[u8]| Transaction::decode(raw).unwrap_err();
+
+        assert_eq!(
+            error(&[0x03, 0xc0]).downcast_ref::<TransactionError>(),
+            Some(&TransactionError::UnsupportedType(0x03))
+        );
+        assert_eq!(
+            error(&[0x02, 0xc0]).downcast_ref::<TransactionError>(),
+            Some(&TransactionError::InvalidField)
+        );
+        assert_eq!(
+            error(&[0x02, 0x80]).downcast_ref::<RlpError>(),
+            Some(&RlpError::ExpectedList)
+        );
+
+        // NOTE: v of a legacy transaction is either 27, 28 or at least 35
+        let fields = [
+            vec![RlpItem::from(0u64); 6],
+            vec![
+                RlpItem::from(30u64),
+                RlpItem::from(1u64),
+                RlpItem::from(1u64),
+            ],
+        ]
+        .concat();
+
+        assert_eq!(
+            error(&RlpItem::List(fields).encode()).downcast_ref::<TransactionError>(),
+            Some(&TransactionError::InvalidSignature)
+        );
+
+        let tx = Transaction {
+            chain_id: Some(5),
+            ..Default::default()
+        };
+        let (state, block) = create_env();
+
+        assert_eq!(
+            tx.validate(&state, &block, SpecId::Cancun),
+            Err(TransactionError::InvalidChainId(1, 5))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_high_s_signature_returns_transaction_error() -> Result<(), Box<dyn Error>> {
+        use k256::{ecdsa::Signature, FieldBytes};
+
+        let raw = sign(
+            0x02,
+            vec![
+                RlpItem::from(1u64),
+                RlpItem::from(0u64),
+                RlpItem::from(2u64),
+                RlpItem::from(10u64),
+                RlpItem::from(21_000u64),
+                RlpItem::from(Address::from(0xbeef)),
+                RlpItem::from(0u64),
+                RlpItem::Bytes(vec![]),
+                RlpItem::List(vec![]),
+            ],
+        )?;
+
+        // NOTE: n - s with the other parity recovers the same key, but is malleable
+        let mut fields = RlpItem::decode(&raw[1..])?.as_list()?.to_vec();
+        let count = fields.len();
+        let signature = Signature::from_scalars(
+            FieldBytes::clone_from_slice(fields[count - 2].as_bytes()?),
+            FieldBytes::clone_from_slice(fields[count - 1].as_bytes()?),
+        )?;
+        let (_, s) = signature.split_scalars();
+
+        fields[count - 3] = RlpItem::from(1 - fields[count - 3].as_u64()?);
+        fields[count - 1] = RlpItem::from((-*s).to_bytes().to_vec());
+
+        let mut raw = vec![0x02];
+        raw.extend(RlpItem::List(fields).encode());
+
+        assert_eq!(
+            Transaction::decode(&raw)
+                .unwrap_err()
+                .downcast_ref::<TransactionError>(),
+            Some(&TransactionError::InvalidSignature)
+        );
+
+        Ok(())
+    }
+
+    // NOTE: helper function, signs a typed transaction with the private key 1
+    fn sign(tx_type: u8, mut fields: Vec<RlpItem>) -> Result<Vec<u8>, Box<dyn Error>> {
+        use k256::ecdsa::SigningKey;
+
+        let mut payload = vec![tx_type];
+        payload.extend(RlpItem::List(fields.clone()).encode());
+
+        let signing_key = SigningKey::from_slice(&Bytes32::from(1).0)?;
+        let hash = crypto::keccak256(&payload);
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&hash.0)?;
+        let (r, s) = signature.split_bytes();
+
+        fields.extend([
+            RlpItem::from(recovery_id.to_byte() as u64),
+            RlpItem::from(r.to_vec()),
+            RlpItem::from(s.to_vec()),
+        ]);
+
+        let mut raw = vec![tx_type];
+        raw.extend(RlpItem::List(fields).encode());
+
+        Ok(raw)
+    }
+
+    // NOTE: helper function, funds the default caller
+    fn create_env() -> (WorldState, BlockEnv) {
+        let mut state = WorldState::new();
+        *state.account_mut(Address::from(DEFAULT_CALLER)) = Account::new(BALANCE);
+
+        (state, BlockEnv::default())
+    }
+}
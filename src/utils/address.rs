@@ -0,0 +1,133 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{bytes32::Bytes32, errors::AddressError};
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub fn zero() -> Self {
+        Self([0; 20])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = hex::decode(s.trim_start_matches("0x")).map_err(|_| AddressError::InvalidStr)?;
+
+        let bytes: [u8; 20] = s.try_into().map_err(|_| AddressError::InvalidStr)?;
+
+        Ok(Address(bytes))
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        s.parse::<Address>().map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<u64> for Address {
+    fn from(value: u64) -> Self {
+        let mut bytes = [0u8; 20];
+        bytes[12..20].copy_from_slice(&value.to_be_bytes());
+        Address(bytes)
+    }
+}
+
+/// Takes the lower 20 bytes of a word, as the EVM does for address operands.
+impl From<Bytes32> for Address {
+    fn from(value: Bytes32) -> Self {
+        let mut bytes = [0u8; 20];
+        bytes.copy_from_slice(&value.0[12..32]);
+        Address(bytes)
+    }
+}
+
+impl From<Address> for Bytes32 {
+    fn from(value: Address) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[12..32].copy_from_slice(&value.0);
+        Bytes32(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_from_str_to_address() -> Result<(), Box<dyn Error>> {
+        let address = "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5".parse::<Address>()?;
+
+        assert_eq!(address.0[0], 0x95);
+        assert_eq!(address.0[19], 0xe5);
+        assert_eq!(
+            address.to_string(),
+            "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str_with_wrong_length_returns_address_error() {
+        let result = "0x9522".parse::<Address>();
+
+        assert!(matches!(result, Err(AddressError::InvalidStr)));
+    }
+
+    #[test]
+    fn test_address_and_bytes32_conversions() -> Result<(), Box<dyn Error>> {
+        let address = "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5".parse::<Address>()?;
+        let word = Bytes32::from(address);
+
+        assert_eq!(
+            word.to_string(),
+            "00000000000000000000000095222290dd7278aa3ddd389cc1e1d165cc4bafe5"
+        );
+        assert_eq!(Address::from(word), address);
+
+        let mut dirty = word;
+        dirty.0[0] = 0xff;
+
+        assert_eq!(Address::from(dirty), address);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_u64_to_address() {
+        let address = Address::from(0x1000);
+
+        assert_eq!(
+            address.to_string(),
+            "0x0000000000000000000000000000000000001000"
+        );
+        assert!(Address::zero().is_zero());
+    }
+}
@@ -5,6 +5,8 @@ use std::{
     str::FromStr,
 };
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use super::errors::Bytes32Error;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -29,6 +31,26 @@ impl Bytes32 {
 
         Ok(result)
     }
+
+    /// Converts to usize, saturating at usize::MAX when the value does not fit.
+    pub fn saturating_to_usize(self) -> usize {
+        let from = 32 - (usize::BITS / 8) as usize;
+
+        if self.0[..from].iter().any(|byte| *byte != 0) {
+            return usize::MAX;
+        }
+
+        usize::from_be_bytes(self.0[from..32].try_into().unwrap())
+    }
+
+    /// Converts to u128, saturating at u128::MAX when the value does not fit.
+    pub fn saturating_to_u128(self) -> u128 {
+        if self.0[..16].iter().any(|byte| *byte != 0) {
+            return u128::MAX;
+        }
+
+        u128::from_be_bytes(self.0[16..32].try_into().unwrap())
+    }
 }
 
 impl FromStr for Bytes32 {
@@ -36,12 +58,38 @@ impl FromStr for Bytes32 {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut bytes = [0u8; 32];
-        let s = hex::decode(s.trim_start_matches("0x")).map_err(|_| Bytes32Error::InvalidStr)?;
+        let s = s.trim_start_matches("0x");
+        // NOTE: trimmed stack items may have an odd number of nibbles
+        let s = if s.len() % 2 == 1 {
+            hex::decode(format!("0{s}"))
+        } else {
+            hex::decode(s)
+        }
+        .map_err(|_| Bytes32Error::InvalidStr)?;
+
+        if s.len() > 32 {
+            return Err(Bytes32Error::InvalidStr);
+        }
+
         bytes[32 - s.len()..].copy_from_slice(&s);
         Ok(Bytes32(bytes))
     }
 }
 
+impl Serialize for Bytes32 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes32 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        s.parse::<Bytes32>().map_err(serde::de::Error::custom)
+    }
+}
+
 impl Display for Bytes32 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", hex::encode(self.0))
@@ -292,6 +340,16 @@ mod tests {
         assert_eq!(result.0.len(), expected.len());
     }
 
+    #[test]
+    fn test_from_str_with_odd_length_to_bytes32() -> Result<(), Box<dyn Error>> {
+        let result = Bytes32::from_str("a")?;
+
+        assert_eq!(result, Bytes32::from(10));
+        assert!(Bytes32::from_str(&"ff".repeat(33)).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_to_string_from_bytes32() {
         let data = "8060202020";
@@ -393,6 +451,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_saturating_to_usize() -> Result<(), Box<dyn Error>> {
+        assert_eq!(Bytes32::from(1024usize).saturating_to_usize(), 1024);
+        assert_eq!(
+            "010000000000000000000000000000000000"
+                .parse::<Bytes32>()?
+                .saturating_to_usize(),
+            usize::MAX
+        );
+        assert_eq!(
+            Bytes32::from(1u128 << 100).saturating_to_u128(),
+            1u128 << 100
+        );
+        assert_eq!(
+            "0100000000000000000000000000000000"
+                .parse::<Bytes32>()?
+                .saturating_to_u128(),
+            u128::MAX
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_serde_bytes32() -> Result<(), Box<dyn Error>> {
+        let result: Bytes32 = serde_json::from_str("\"0x0400\"")?;
+
+        assert_eq!(result, Bytes32::from(1024));
+        assert_eq!(
+            serde_json::to_string(&result)?,
+            "\"0x0000000000000000000000000000000000000000000000000000000000000400\""
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_cast_with_size() -> Result<(), Box<dyn Error>> {
         let data = "8060202020";
@@ -1,25 +1,339 @@
-use std::error::Error;
+use std::{error::Error, path::PathBuf};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
-use crate::vm::Vm;
+use crate::{
+    env::Env,
+    gas::{Gas, DEFAULT_GAS_LIMIT},
+    spec::SpecId,
+    state::WorldState,
+    transaction::{AccessList, AccessListResult, Transaction, TransactionResult},
+    utils::{address::Address, bytes32::Bytes32, history::ProfileFormat},
+    vm::Vm,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
-    #[arg(short, long)]
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["raw_transaction", "tx_file"],
+        default_value = ""
+    )]
     bytecode: String,
 
     #[arg(short, long)]
     verbose: bool,
+
+    #[arg(short, long, default_value_t = DEFAULT_GAS_LIMIT)]
+    gas_limit: u64,
+
+    #[arg(short, long, default_value = "")]
+    calldata: String,
+
+    #[arg(short, long, value_enum, num_args = 0..=1, default_missing_value = "text")]
+    profile: Option<ProfileFormat>,
+
+    #[arg(long, value_enum, default_value_t = SpecId::default())]
+    spec: SpecId,
+
+    #[arg(long)]
+    env: Option<PathBuf>,
+
+    #[arg(long)]
+    state: Option<PathBuf>,
+
+    #[arg(long)]
+    coinbase: Option<Address>,
+
+    #[arg(long)]
+    timestamp: Option<u64>,
+
+    #[arg(long)]
+    block_number: Option<u64>,
+
+    #[arg(long)]
+    prevrandao: Option<Bytes32>,
+
+    #[arg(long)]
+    block_gas_limit: Option<u64>,
+
+    #[arg(long)]
+    chain_id: Option<u64>,
+
+    #[arg(long)]
+    basefee: Option<u128>,
+
+    #[arg(long, value_parser = parse_block_hash)]
+    block_hash: Vec<(u64, Bytes32)>,
+
+    #[arg(long)]
+    address: Option<Address>,
+
+    #[arg(long)]
+    origin: Option<Address>,
+
+    #[arg(long)]
+    caller: Option<Address>,
+
+    #[arg(long)]
+    value: Option<u128>,
+
+    #[arg(long, conflicts_with_all = ["max_fee_per_gas", "max_priority_fee_per_gas"])]
+    gas_price: Option<u128>,
+
+    #[arg(long, conflicts_with = "deploy")]
+    transaction: bool,
+
+    #[arg(long)]
+    deploy: bool,
+
+    #[arg(long, requires = "deploy", default_value = "")]
+    constructor_args: String,
+
+    #[arg(long, requires = "deploy", default_value_t = 0)]
+    call_value: u128,
+
+    #[arg(long)]
+    nonce: Option<u64>,
+
+    #[arg(long)]
+    max_fee_per_gas: Option<u128>,
+
+    #[arg(long)]
+    max_priority_fee_per_gas: Option<u128>,
+
+    #[arg(long, requires = "transaction", value_parser = parse_access_list)]
+    access_list: Option<AccessList>,
+
+    #[arg(long, conflicts_with_all = ["transaction", "deploy"])]
+    raw_transaction: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["transaction", "deploy", "raw_transaction"])]
+    tx_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// Runs the bytecode as a transaction and prints the access list it would benefit from
+    CreateAccessList,
+}
+
+impl Args {
+    pub fn is_transaction(&self) -> bool {
+        self.transaction
+    }
+
+    pub fn is_deploy(&self) -> bool {
+        self.deploy
+    }
+
+    /// Returns true if the transaction is given as a signed raw transaction or a file.
+    pub fn is_replay(&self) -> bool {
+        self.raw_transaction.is_some() || self.tx_file.is_some()
+    }
+
+    pub fn command(&self) -> Option<Command> {
+        self.command
+    }
+
+    /// Loads the env file if given and applies the single value overrides on top of it.
+    fn env(&self) -> Result<Env, Box<dyn Error>> {
+        let mut env = match &self.env {
+            Some(path) => Env::from_file(path)?,
+            None => Env::default(),
+        };
+
+        let block = &mut env.block;
+        if let Some(coinbase) = self.coinbase {
+            block.coinbase = coinbase;
+        }
+        if let Some(timestamp) = self.timestamp {
+            block.timestamp = timestamp;
+        }
+        if let Some(number) = self.block_number {
+            block.number = number;
+        }
+        if let Some(prevrandao) = self.prevrandao {
+            block.prevrandao = prevrandao;
+        }
+        if let Some(gas_limit) = self.block_gas_limit {
+            block.gas_limit = gas_limit;
+        }
+        if let Some(chain_id) = self.chain_id {
+            block.chain_id = chain_id;
+        }
+        if let Some(basefee) = self.basefee {
+            block.basefee = basefee;
+        }
+        block.block_hashes.extend(self.block_hash.iter().copied());
+
+        let tx = &mut env.tx;
+        if let Some(address) = self.address {
+            tx.address = address;
+        }
+        if let Some(origin) = self.origin {
+            tx.origin = origin;
+        }
+        if let Some(caller) = self.caller {
+            tx.caller = caller;
+        }
+        if let Some(value) = self.value {
+            tx.value = value;
+        }
+        if let Some(gas_price) = self.gas_price {
+            tx.gas_price = gas_price;
+        }
+
+        Ok(env)
+    }
+
+    fn world_state(&self) -> Result<WorldState, Box<dyn Error>> {
+        match &self.state {
+            Some(path) => WorldState::from_file(path),
+            None => Ok(WorldState::new()),
+        }
+    }
+
+    /// Builds a transaction from the env caller with the value and fee fields of the args.
+    fn transaction(&self, env: &Env, to: Option<Address>, data: Vec<u8>) -> Transaction {
+        Transaction {
+            caller: env.tx.caller,
+            to,
+            value: env.tx.value,
+            data,
+            nonce: self.nonce,
+            gas_limit: self.gas_limit,
+            chain_id: None,
+            gas_price: (self.max_fee_per_gas.is_none() && self.max_priority_fee_per_gas.is_none())
+                .then_some(env.tx.gas_price),
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            access_list: self.access_list.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Builds a transaction to the bytecode installed at the env address, along with
+    /// the state and env it runs in.
+    fn call(&self) -> Result<(Transaction, WorldState, Env), Box<dyn Error>> {
+        let env = self.env()?;
+
+        let mut state = self.world_state()?;
+        state.account_mut(env.tx.address).code =
+            hex::decode(self.bytecode.trim_start_matches("0x"))?;
+
+        let calldata = hex::decode(self.calldata.trim_start_matches("0x"))?;
+        let tx = self.transaction(&env, Some(env.tx.address), calldata);
+
+        Ok((tx, state, env))
+    }
+}
+
+fn parse_block_hash(s: &str) -> Result<(u64, Bytes32), Box<dyn Error + Send + Sync>> {
+    let (number, hash) = s
+        .split_once('=')
+        .ok_or("a block hash must be given as NUMBER=HASH")?;
+
+    Ok((number.parse::<u64>()?, hash.parse::<Bytes32>()?))
+}
+
+fn parse_access_list(s: &str) -> Result<AccessList, Box<dyn Error + Send + Sync>> {
+    Ok(serde_json::from_str(s)?)
 }
 
 pub trait AppBuilder {
     fn get_args(&self) -> &Args;
 
-    fn build(&self) -> Result<Vm, Box<dyn Error>> {
+    fn build(&self) -> Result<Vm<'_>, Box<dyn Error>> {
+        let args = self.get_args();
+
+        let mut vm = Vm::new(&args.bytecode, args.verbose)?;
+        vm.gas = Gas::new(args.gas_limit);
+        vm.profile = args.profile;
+        vm.spec = args.spec;
+        vm.calldata = hex::decode(args.calldata.trim_start_matches("0x"))?;
+
+        let env = args.env()?;
+        vm.block = env.block;
+        vm.tx = env.tx;
+
+        if let Some(path) = &args.state {
+            vm.state = WorldState::from_file(path)?;
+        }
+
+        // NOTE: the given bytecode is the code of the executing account
+        vm.state.account_mut(vm.tx.address).code = vm.code.clone();
+
+        Ok(vm)
+    }
+
+    /// Runs the bytecode as the code of the recipient of a transaction sent by the caller.
+    fn transact(&self) -> Result<TransactionResult, Box<dyn Error>> {
+        let args = self.get_args();
+        let (tx, mut state, env) = args.call()?;
+
+        tx.execute(&mut state, &env.block, args.spec)
+    }
+
+    /// Generates an access list for the transaction built by `transact`.
+    fn create_access_list(&self) -> Result<AccessListResult, Box<dyn Error>> {
+        let args = self.get_args();
+        let (tx, state, env) = args.call()?;
+
+        tx.create_access_list(&state, &env.block, args.spec)
+    }
+
+    /// Deploys the bytecode as init code followed by the constructor arguments with the
+    /// value, then calls the deployed contract with the calldata and the call value on
+    /// top of the resulting state. The call is skipped when the deployment fails.
+    fn deploy(&self) -> Result<(TransactionResult, Option<TransactionResult>), Box<dyn Error>> {
+        let args = self.get_args();
+        let env = args.env()?;
+        let mut state = args.world_state()?;
+
+        let mut initcode = hex::decode(args.bytecode.trim_start_matches("0x"))?;
+        initcode.extend(hex::decode(args.constructor_args.trim_start_matches("0x"))?);
+
+        let deployment = args
+            .transaction(&env, None, initcode)
+            .execute(&mut state, &env.block, args.spec)?;
+
+        let Some(address) = deployment.contract_address else {
+            return Ok((deployment, None));
+        };
+
+        let calldata = hex::decode(args.calldata.trim_start_matches("0x"))?;
+        let call = Transaction {
+            nonce: None,
+            value: args.call_value,
+            ..args.transaction(&env, Some(address), calldata)
+        }
+        .execute(&mut state, &env.block, args.spec)?;
+
+        Ok((deployment, Some(call)))
+    }
+
+    /// Loads the transaction file or decodes the signed raw transaction, and executes
+    /// it against the pre-state.
+    fn replay(&self) -> Result<(Transaction, TransactionResult), Box<dyn Error>> {
         let args = self.get_args();
-        Vm::new(&args.bytecode, args.verbose)
+        let env = args.env()?;
+        let mut state = args.world_state()?;
+
+        let tx = match &args.tx_file {
+            Some(path) => Transaction::from_file(path)?,
+            None => {
+                let raw = args.raw_transaction.as_deref().unwrap_or_default();
+                Transaction::decode(&hex::decode(raw.trim_start_matches("0x"))?)?
+            }
+        };
+        let result = tx.execute(&mut state, &env.block, args.spec)?;
+
+        Ok((tx, result))
     }
 }
 
@@ -30,14 +344,21 @@ impl AppBuilder for Args {
 }
 
 #[cfg(test)]
+// NOTE: the original tests compare against bool literals
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use std::ffi::OsString;
 
+    use crate::{
+        env::{TxEnv, DEFAULT_CALLER},
+        utils::crypto,
+    };
+
     use super::*;
 
     #[test]
     fn it_initializes_args() -> Result<(), Box<dyn Error>> {
-        let args = get_mock_args(&["cubipods", "--bytecode", "0x600160026003610101"])?;
+        let args = get_mock_args(["cubipods", "--bytecode", "0x600160026003610101"])?;
 
         let mut vm = args.build()?;
 
@@ -47,6 +368,399 @@ mod tests {
         assert_eq!(vm.stack.length, 4);
         assert_eq!(vm.stack.peek().unwrap(), "0101");
         assert_eq!(args.verbose, false);
+        assert_eq!(vm.gas.used(), 12);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_gas_limit() -> Result<(), Box<dyn Error>> {
+        let args = get_mock_args(["cubipods", "--bytecode", "0x6001", "--gas-limit", "2"])?;
+
+        let mut vm = args.build()?;
+
+        assert!(vm.run().is_err());
+        assert_eq!(vm.gas.limit(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_profile() -> Result<(), Box<dyn Error>> {
+        let args = get_mock_args(["cubipods", "--bytecode", "0x6001", "--profile"])?;
+        assert_eq!(args.build()?.profile, Some(ProfileFormat::Text));
+
+        let args = get_mock_args(["cubipods", "--bytecode", "0x6001", "--profile", "json"])?;
+        assert_eq!(args.build()?.profile, Some(ProfileFormat::Json));
+
+        let args = get_mock_args(["cubipods", "--bytecode", "0x6001"])?;
+        assert_eq!(args.build()?.profile, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_spec() -> Result<(), Box<dyn Error>> {
+        let args = get_mock_args(["cubipods", "--bytecode", "0x5f"])?;
+        assert_eq!(args.build()?.spec, SpecId::Cancun);
+
+        let args = get_mock_args(["cubipods", "--bytecode", "0x5f", "--spec", "london"])?;
+        let mut vm = args.build()?;
+
+        assert_eq!(vm.spec, SpecId::London);
+        assert!(vm.run().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_executes_bytecode_as_transaction() -> Result<(), Box<dyn Error>> {
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x602a600055",
+            "--calldata",
+            "0x0001",
+            "--transaction",
+        ])?;
+
+        assert!(args.is_transaction());
+
+        let result = args.transact()?;
+
+        assert_eq!(result.intrinsic_gas, 21_020);
+        assert_eq!(result.gas_used, 43_126);
+
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x00",
+            "--transaction",
+            "--nonce",
+            "1",
+        ])?;
+
+        assert!(args.transact().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_deploys_bytecode_and_calls_it() -> Result<(), Box<dyn Error>> {
+        // NOTE: the constructor stores its argument in slot 0, the runtime returns slot 0
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x602060203803600039600051600055600b601b600039600b6000f360005460005260206000f3",
+            "--constructor-args",
+            "0x000000000000000000000000000000000000000000000000000000000000002a",
+            "--deploy",
+        ])?;
+
+        assert!(args.is_deploy());
+
+        let (deployment, call) = args.deploy()?;
+        let call = call.unwrap();
+
+        assert_eq!(
+            deployment.contract_address,
+            Some(crypto::create_address(Address::from(DEFAULT_CALLER), 0))
+        );
+        assert!(!call.reverted);
+        assert_eq!(call.output, Bytes32::from(42).0);
+
+        // NOTE: the init code reverts so the call is skipped
+        let args = get_mock_args(["cubipods", "--bytecode", "0x60006000fd", "--deploy"])?;
+        let (deployment, call) = args.deploy()?;
+
+        assert!(deployment.reverted);
+        assert!(call.is_none());
+
+        let args = get_mock_args(["cubipods", "-b", "0x00", "--deploy", "--transaction"]);
+
+        assert!(args.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_deploys_with_value_and_calls_with_call_value() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("cubipods_deploy_state.json");
+        std::fs::write(
+            &path,
+            r#"{ "0x0000000000000000000000000000000000002000": { "balance": 100 } }"#,
+        )?;
+
+        // NOTE: the constructor stores the call value in slot 0, the runtime returns its balance
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x346000556009601060003960096000f34760005260206000f3",
+            "--deploy",
+            "--value",
+            "5",
+            "--call-value",
+            "2",
+            "--state",
+            path.to_str().unwrap(),
+        ])?;
+
+        let (deployment, call) = args.deploy()?;
+        let call = call.unwrap();
+
+        assert!(!deployment.reverted);
+        assert_eq!(call.output, Bytes32::from(7).0);
+
+        let args = get_mock_args(["cubipods", "-b", "0x00", "--call-value", "1"]);
+
+        assert!(args.is_err());
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_replays_raw_transaction() -> Result<(), Box<dyn Error>> {
+        // NOTE: the EIP-155 example signed with the private key 0x4646...46
+        let raw = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000\
+                   8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f\
+                   761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+        let sender = "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f";
+
+        let path = std::env::temp_dir().join("cubipods_replay_state.json");
+        std::fs::write(
+            &path,
+            format!(r#"{{ "{sender}": {{ "balance": 2000000000000000000, "nonce": 9 }} }}"#),
+        )?;
+
+        let args = get_mock_args([
+            "cubipods",
+            "--raw-transaction",
+            raw,
+            "--state",
+            path.to_str().unwrap(),
+        ])?;
+
+        assert!(args.is_replay());
+
+        let (tx, result) = args.replay()?;
+
+        assert_eq!(tx.caller, sender.parse()?);
+        assert!(!result.reverted);
+        assert_eq!(result.gas_used, 21_000);
+
+        // NOTE: without the pre-state the sender has neither the nonce nor the funds
+        let args = get_mock_args(["cubipods", "--raw-transaction", raw])?;
+
+        assert!(args.replay().is_err());
+        assert!(get_mock_args(["cubipods"]).is_err());
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_executes_transaction_file() -> Result<(), Box<dyn Error>> {
+        let state_path = std::env::temp_dir().join("cubipods_tx_file_state.json");
+        std::fs::write(
+            &state_path,
+            r#"{ "0x000000000000000000000000000000000000beef": { "code": "0x60015450" } }"#,
+        )?;
+
+        let tx_path = std::env::temp_dir().join("cubipods_tx_file.json");
+        std::fs::write(
+            &tx_path,
+            r#"{
+                "to": "0x000000000000000000000000000000000000beef",
+                "access_list": [
+                    {
+                        "address": "0x000000000000000000000000000000000000beef",
+                        "storageKeys": ["0x01"]
+                    }
+                ]
+            }"#,
+        )?;
+
+        let args = get_mock_args([
+            "cubipods",
+            "--tx-file",
+            tx_path.to_str().unwrap(),
+            "--state",
+            state_path.to_str().unwrap(),
+        ])?;
+
+        assert!(args.is_replay());
+
+        let (tx, result) = args.replay()?;
+
+        // NOTE: access list (2400 + 1900) + PUSH1, POP (5) + warm SLOAD (100)
+        assert_eq!(tx.access_list.len(), 1);
+        assert_eq!(result.intrinsic_gas, 25_300);
+        assert_eq!(result.gas_used, 25_405);
+
+        std::fs::remove_file(state_path)?;
+        std::fs::remove_file(tx_path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_access_list() -> Result<(), Box<dyn Error>> {
+        let access_list = r#"[{ "address": "0x0000000000000000000000000000000000001000", "storageKeys": ["0x00"] }]"#;
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x602a600055",
+            "--transaction",
+            "--access-list",
+            access_list,
+        ])?;
+
+        let result = args.transact()?;
+
+        // NOTE: access list (2400 + 1900) + 2 pushes (6) + warm SSTORE (20000)
+        assert_eq!(result.intrinsic_gas, 25_300);
+        assert_eq!(result.gas_used, 45_306);
+
+        let args = get_mock_args(["cubipods", "--bytecode", "0x00", "--access-list", "[]"]);
+
+        assert!(args.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_creates_access_list_from_args() -> Result<(), Box<dyn Error>> {
+        // NOTE: loads the balance of 0xcafe
+        let args = get_mock_args(["cubipods", "--bytecode", "0x61cafe31", "create-access-list"])?;
+
+        assert_eq!(args.command(), Some(Command::CreateAccessList));
+
+        let result = args.create_access_list()?;
+
+        assert_eq!(result.access_list.len(), 1);
+        assert_eq!(result.access_list[0].address, Address::from(0xcafe));
+        // NOTE: the access list (2400) saves the cold access surcharge (2500)
+        assert_eq!(result.gas_used_without_access_list, 23_603);
+        assert_eq!(result.gas_used, 23_503);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_calldata() -> Result<(), Box<dyn Error>> {
+        let args = get_mock_args(["cubipods", "--bytecode", "0x36", "--calldata", "0xa9059cbb"])?;
+
+        let mut vm = args.build()?;
+        vm.run()?;
+
+        assert_eq!(vm.calldata, vec![0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(vm.stack.peek().unwrap(), "4");
+
+        let args = get_mock_args(["cubipods", "--bytecode", "0x36", "--calldata", "0xzz"])?;
+
+        assert!(args.build().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_block_env() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("cubipods_block_env.json");
+        std::fs::write(
+            &path,
+            r#"{ "block": { "number": 100, "timestamp": 1700000000, "chain_id": 10 } }"#,
+        )?;
+
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x4342",
+            "--env",
+            path.to_str().unwrap(),
+            "--block-number",
+            "200",
+            "--coinbase",
+            "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+            "--block-hash",
+            "199=0xabcd",
+        ])?;
+
+        let vm = args.build()?;
+
+        assert_eq!(vm.block.number, 200);
+        assert_eq!(vm.block.timestamp, 1_700_000_000);
+        assert_eq!(vm.block.chain_id, 10);
+        assert_eq!(vm.block.block_hash(199), Bytes32::from(0xabcd));
+        assert_eq!(
+            vm.block.coinbase,
+            "95222290dd7278aa3ddd389cc1e1d165cc4bafe5".parse::<Address>()?
+        );
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_tx_env() -> Result<(), Box<dyn Error>> {
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x3334",
+            "--caller",
+            "0x00000000000000000000000000000000000000aa",
+            "--value",
+            "1000",
+        ])?;
+
+        let mut vm = args.build()?;
+        vm.run()?;
+
+        assert_eq!(vm.tx.caller, Address::from(0xaa));
+        assert_eq!(vm.tx.origin, TxEnv::default().origin);
+        assert_eq!(vm.stack.pop()?.1, "3e8");
+        assert_eq!(vm.stack.pop()?.1, "aa");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_initializes_args_with_state() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("cubipods_state.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "0x0000000000000000000000000000000000001000": {
+                    "balance": 100,
+                    "storage": { "0x01": "0x29" }
+                }
+            }"#,
+        )?;
+
+        let args = get_mock_args([
+            "cubipods",
+            "--bytecode",
+            "0x600154600101600155",
+            "--state",
+            path.to_str().unwrap(),
+        ])?;
+
+        let mut vm = args.build()?;
+        vm.run()?;
+
+        let address = vm.tx.address;
+
+        assert_eq!(vm.state.balance(&address), 100);
+        assert_eq!(vm.state.code(&address), vm.code.as_slice());
+        assert_eq!(
+            vm.state.sload(&address, Bytes32::from(1)),
+            Bytes32::from(42)
+        );
+
+        std::fs::remove_file(path)?;
 
         Ok(())
     }
@@ -57,6 +771,6 @@ mod tests {
         I: IntoIterator<Item = T>,
         T: Into<OsString> + Clone,
     {
-        return Ok(Args::try_parse_from(itr)?);
+        Ok(Args::try_parse_from(itr)?)
     }
 }
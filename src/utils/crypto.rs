@@ -0,0 +1,227 @@
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use tiny_keccak::{Hasher, Keccak};
+
+use super::{address::Address, bytes32::Bytes32, rlp::RlpItem};
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+pub fn keccak256(data: &[u8]) -> Bytes32 {
+    let mut result = [0u8; 32];
+
+    let mut keccak = Keccak::v256();
+    keccak.update(data);
+    keccak.finalize(&mut result);
+
+    Bytes32(result)
+}
+
+/// The BLAKE2b compression function F with a configurable number of rounds (RFC 7693).
+pub fn blake2b_compress(rounds: u32, h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], f: bool) {
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+
+    if f {
+        v[14] = !v[14];
+    }
+
+    for round in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[round % 10];
+
+        blake2b_mix(&mut v, [0, 4, 8, 12], m[s[0]], m[s[1]]);
+        blake2b_mix(&mut v, [1, 5, 9, 13], m[s[2]], m[s[3]]);
+        blake2b_mix(&mut v, [2, 6, 10, 14], m[s[4]], m[s[5]]);
+        blake2b_mix(&mut v, [3, 7, 11, 15], m[s[6]], m[s[7]]);
+        blake2b_mix(&mut v, [0, 5, 10, 15], m[s[8]], m[s[9]]);
+        blake2b_mix(&mut v, [1, 6, 11, 12], m[s[10]], m[s[11]]);
+        blake2b_mix(&mut v, [2, 7, 8, 13], m[s[12]], m[s[13]]);
+        blake2b_mix(&mut v, [3, 4, 9, 14], m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+fn blake2b_mix(v: &mut [u64; 16], [a, b, c, d]: [usize; 4], x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// Derives the address of a contract created with CREATE from
+/// keccak256(rlp([sender, nonce])).
+pub fn create_address(sender: Address, nonce: u64) -> Address {
+    let encoded = RlpItem::from(vec![RlpItem::from(sender), RlpItem::from(nonce)]).encode();
+
+    Address::from(keccak256(&encoded))
+}
+
+/// Derives the address of a contract created with CREATE2 from
+/// keccak256(0xff ++ sender ++ salt ++ keccak256(init_code)) (EIP-1014).
+pub fn create2_address(sender: Address, salt: Bytes32, init_code: &[u8]) -> Address {
+    let mut data = Vec::with_capacity(85);
+    data.push(0xff);
+    data.extend_from_slice(&sender.0);
+    data.extend_from_slice(&salt.0);
+    data.extend_from_slice(&keccak256(init_code).0);
+
+    Address::from(keccak256(&data))
+}
+
+/// Recovers the uncompressed public key, without its 0x04 prefix, which signed
+/// the hash. The recovery id must be 0 or 1, and invalid signatures return None.
+pub fn recover_public_key(
+    hash: &Bytes32,
+    signature: &[u8; 64],
+    recovery_id: u8,
+) -> Option<[u8; 64]> {
+    if recovery_id > 1 {
+        return None;
+    }
+
+    let signature = Signature::from_slice(signature).ok()?;
+    let recovery_id = RecoveryId::from_byte(recovery_id)?;
+
+    // NOTE: k256 only accepts low-s signatures, and negating s flips the parity of R
+    let (signature, recovery_id) = match signature.normalize_s() {
+        Some(normalized) => (normalized, RecoveryId::new(!recovery_id.is_y_odd(), false)),
+        None => (signature, recovery_id),
+    };
+
+    let key = VerifyingKey::recover_from_prehash(&hash.0, &signature, recovery_id).ok()?;
+
+    key.to_encoded_point(false).as_bytes()[1..].try_into().ok()
+}
+
+/// Returns true if s is at most half of the curve order, as required for
+/// transaction signatures since Homestead (EIP-2).
+pub fn is_low_s(signature: &[u8; 64]) -> bool {
+    Signature::from_slice(signature).is_ok_and(|signature| signature.normalize_s().is_none())
+}
+
+/// Derives the address of a public key from the last 20 bytes of its keccak256 hash.
+pub fn public_key_to_address(public_key: &[u8; 64]) -> Address {
+    Address::from(keccak256(public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_hashes_with_keccak256() {
+        assert_eq!(
+            keccak256(b"hello").to_string(),
+            "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8"
+        );
+        assert_eq!(
+            keccak256(&[]).to_string(),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn it_recovers_signer_address() -> Result<(), Box<dyn std::error::Error>> {
+        use k256::ecdsa::SigningKey;
+
+        // NOTE: the private key 1 belongs to 0x7e5f4552091a69125d5dfcb7b8c2659029395bdf
+        let signing_key = SigningKey::from_slice(&Bytes32::from(1).0)?;
+        let hash = keccak256(b"hello");
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&hash.0)?;
+
+        let public_key =
+            recover_public_key(&hash, &signature.to_bytes().into(), recovery_id.to_byte()).unwrap();
+
+        assert_eq!(
+            public_key_to_address(&public_key),
+            "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf".parse::<Address>()?
+        );
+
+        // NOTE: the high-s form of the signature recovers the same key with the other parity
+        let (r, s) = signature.split_scalars();
+        let high_s = Signature::from_scalars(r.to_bytes(), (-*s).to_bytes())?;
+
+        assert_eq!(
+            recover_public_key(&hash, &high_s.to_bytes().into(), 1 - recovery_id.to_byte()),
+            Some(public_key)
+        );
+        assert!(is_low_s(&signature.to_bytes().into()));
+        assert!(!is_low_s(&high_s.to_bytes().into()));
+        assert_eq!(
+            recover_public_key(&hash, &[0; 64], recovery_id.to_byte()),
+            None
+        );
+        assert_eq!(
+            recover_public_key(&hash, &signature.to_bytes().into(), 2),
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_derives_create_addresses() -> Result<(), Box<dyn std::error::Error>> {
+        let sender = "0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0".parse::<Address>()?;
+
+        assert_eq!(
+            create_address(sender, 0),
+            "0xcd234a471b72ba2f1ccf0a70fcaba648a5eecd8d".parse::<Address>()?
+        );
+        assert_eq!(
+            create_address(sender, 1),
+            "0x343c43a37d37dff08ae8c4a11544c718abb4fcf8".parse::<Address>()?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_derives_create2_addresses() -> Result<(), Box<dyn std::error::Error>> {
+        // NOTE: examples from EIP-1014
+        assert_eq!(
+            create2_address(Address::zero(), Bytes32::from(0), &[0x00]),
+            "0x4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38".parse::<Address>()?
+        );
+        assert_eq!(
+            create2_address(
+                "0xdeadbeef00000000000000000000000000000000".parse::<Address>()?,
+                Bytes32::from(0),
+                &[0x00]
+            ),
+            "0xb928f69bb1d91cd65274e3c79d8986362984fda3".parse::<Address>()?
+        );
+
+        Ok(())
+    }
+}
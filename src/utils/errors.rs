@@ -1,6 +1,6 @@
 use std::{error::Error, fmt::Display};
 
-use crate::instruction::InstructionType;
+use crate::{instruction::InstructionType, spec::SpecId};
 
 #[derive(Debug, PartialEq)]
 pub enum LexerError {
@@ -85,6 +85,10 @@ impl Error for StackError {}
 pub enum VmError<'a> {
     ShallowStack(&'a InstructionType),
     IncompatibleSize(InstructionType),
+    WriteProtection(InstructionType),
+    ReturnDataOutOfBounds,
+    InitCodeSizeLimit,
+    NotActivated(InstructionType, SpecId),
 }
 
 impl<'a> Display for VmError<'a> {
@@ -96,6 +100,26 @@ impl<'a> Display for VmError<'a> {
             VmError::IncompatibleSize(instruction_type) => {
                 write!(f, "Size exceeds {:?} opcode limit.", instruction_type)
             }
+            VmError::WriteProtection(instruction_type) => {
+                write!(
+                    f,
+                    "Cannot call {:?} opcode in a static context.",
+                    instruction_type
+                )
+            }
+            VmError::ReturnDataOutOfBounds => {
+                write!(f, "The copy exceeds the return data size.")
+            }
+            VmError::InitCodeSizeLimit => {
+                write!(f, "Init code exceeds the maximum init code size.")
+            }
+            VmError::NotActivated(instruction_type, spec) => {
+                write!(
+                    f,
+                    "{:?} opcode is not activated in {:?}.",
+                    instruction_type, spec
+                )
+            }
         }
     }
 }
@@ -139,3 +163,274 @@ impl Display for Bytes32Error {
         }
     }
 }
+
+#[derive(Debug, PartialEq)]
+pub enum GasError {
+    OutOfGas,
+}
+
+impl Error for GasError {}
+
+impl Display for GasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GasError::OutOfGas => {
+                write!(f, "The execution ran out of gas.")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AddressError {
+    InvalidStr,
+}
+
+impl Error for AddressError {}
+
+impl Display for AddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressError::InvalidStr => {
+                write!(f, "An address must be 20 bytes of hex.")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StateError {
+    AccountAlreadyExists,
+    InsufficientBalance,
+}
+
+impl Error for StateError {}
+
+impl Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::AccountAlreadyExists => {
+                write!(f, "An account already exists at the given address.")
+            }
+            StateError::InsufficientBalance => {
+                write!(f, "The account balance is insufficient for the transfer.")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TransactionError {
+    InvalidNonce(u64, u64),
+    InsufficientFunds,
+    IntrinsicGasTooLow,
+    GasLimitExceedsBlock,
+    FeeCapTooLow,
+    PriorityFeeTooHigh,
+    UnsupportedAccessList,
+    InvalidChainId(u64, u64),
+    UnsupportedType(u8),
+    InvalidField,
+    InvalidSignature,
+    UnsupportedDynamicFee,
+    ConflictingFees,
+    InitCodeSizeLimit,
+}
+
+impl Error for TransactionError {}
+
+impl Display for TransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionError::InvalidNonce(expected, given) => {
+                write!(
+                    f,
+                    "The sender nonce is {}, but the transaction has {}.",
+                    expected, given
+                )
+            }
+            TransactionError::InsufficientFunds => {
+                write!(f, "The sender cannot pay for the gas and the value.")
+            }
+            TransactionError::IntrinsicGasTooLow => {
+                write!(f, "The gas limit is lower than the intrinsic gas.")
+            }
+            TransactionError::GasLimitExceedsBlock => {
+                write!(f, "The gas limit exceeds the block gas limit.")
+            }
+            TransactionError::FeeCapTooLow => {
+                write!(f, "The max fee per gas is lower than the base fee.")
+            }
+            TransactionError::PriorityFeeTooHigh => {
+                write!(
+                    f,
+                    "The max priority fee per gas exceeds the max fee per gas."
+                )
+            }
+            TransactionError::UnsupportedAccessList => {
+                write!(f, "Access lists are only supported since Berlin.")
+            }
+            TransactionError::InvalidChainId(expected, given) => {
+                write!(
+                    f,
+                    "The chain id is {}, but the transaction is signed for {}.",
+                    expected, given
+                )
+            }
+            TransactionError::UnsupportedType(tx_type) => {
+                write!(f, "The transaction type {:#04x} is not supported.", tx_type)
+            }
+            TransactionError::InvalidField => {
+                write!(f, "A field of the raw transaction is malformed.")
+            }
+            TransactionError::InvalidSignature => {
+                write!(f, "The sender cannot be recovered from the signature.")
+            }
+            TransactionError::UnsupportedDynamicFee => {
+                write!(f, "Dynamic fee fields are only supported since London.")
+            }
+            TransactionError::ConflictingFees => {
+                write!(
+                    f,
+                    "The transaction sets both a gas price and dynamic fee fields."
+                )
+            }
+            TransactionError::InitCodeSizeLimit => {
+                write!(f, "The init code exceeds the maximum init code size.")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EofError {
+    InvalidMagic,
+    InvalidVersion,
+    InvalidHeader,
+    InvalidContainerSize,
+    InvalidTypeSection,
+    InvalidInstruction(u8),
+    TruncatedImmediate,
+    InvalidRelativeJump,
+    InvalidCodeSectionIndex,
+    InvalidStackHeight,
+    MaxStackHeightMismatch,
+    NoTerminatingInstruction,
+    UnreachableCode,
+}
+
+impl Error for EofError {}
+
+impl Display for EofError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EofError::InvalidMagic => {
+                write!(f, "An EOF container must start with 0xef00.")
+            }
+            EofError::InvalidVersion => {
+                write!(f, "Only EOF version 1 is supported.")
+            }
+            EofError::InvalidHeader => {
+                write!(f, "The EOF header is malformed.")
+            }
+            EofError::InvalidContainerSize => {
+                write!(f, "The EOF container size does not match its header.")
+            }
+            EofError::InvalidTypeSection => {
+                write!(f, "The EOF type section is invalid.")
+            }
+            EofError::InvalidInstruction(opcode) => {
+                write!(f, "Opcode 0x{:02x} is not allowed in EOF code.", opcode)
+            }
+            EofError::TruncatedImmediate => {
+                write!(f, "An instruction immediate exceeds the code section.")
+            }
+            EofError::InvalidRelativeJump => {
+                write!(f, "A relative jump does not target an instruction.")
+            }
+            EofError::InvalidCodeSectionIndex => {
+                write!(f, "CALLF targets a code section which does not exist.")
+            }
+            EofError::InvalidStackHeight => {
+                write!(f, "The stack height is inconsistent or underflows.")
+            }
+            EofError::MaxStackHeightMismatch => {
+                write!(
+                    f,
+                    "The maximum stack height does not match the type section."
+                )
+            }
+            EofError::NoTerminatingInstruction => {
+                write!(
+                    f,
+                    "A code section does not end with a terminating instruction."
+                )
+            }
+            EofError::UnreachableCode => {
+                write!(f, "A code section contains unreachable code.")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PrecompileError {
+    OutOfGas,
+    InvalidInput,
+}
+
+impl Error for PrecompileError {}
+
+impl Display for PrecompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrecompileError::OutOfGas => {
+                write!(f, "The precompile ran out of gas.")
+            }
+            PrecompileError::InvalidInput => {
+                write!(f, "Invalid input is provided to the precompile.")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RlpError {
+    UnexpectedEnd,
+    TrailingBytes,
+    NonCanonical,
+    ExpectedBytes,
+    ExpectedList,
+    IntegerOverflow,
+    TooDeep,
+}
+
+impl Error for RlpError {}
+
+impl Display for RlpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RlpError::UnexpectedEnd => {
+                write!(f, "The RLP input ends before the item does.")
+            }
+            RlpError::TrailingBytes => {
+                write!(f, "The RLP input has bytes after the item.")
+            }
+            RlpError::NonCanonical => {
+                write!(f, "The RLP item is not encoded in its shortest form.")
+            }
+            RlpError::ExpectedBytes => {
+                write!(f, "A byte string is expected, but a list is found.")
+            }
+            RlpError::ExpectedList => {
+                write!(f, "A list is expected, but a byte string is found.")
+            }
+            RlpError::IntegerOverflow => {
+                write!(f, "The RLP integer is too large.")
+            }
+            RlpError::TooDeep => {
+                write!(f, "The RLP lists are nested too deeply.")
+            }
+        }
+    }
+}
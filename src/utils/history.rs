@@ -1,6 +1,13 @@
-use std::error::Error;
+use std::{collections::HashMap, error::Error};
 
-use crate::{instruction::InstructionType, vm::Vm};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{
+    gas,
+    instruction::InstructionType,
+    vm::{Log, Vm},
+};
 
 use super::{bytes32::Bytes32, errors::HistoryError};
 
@@ -9,6 +16,22 @@ pub struct History {
     registry: Vec<Registry>,
     memory_locations: Vec<Bytes32>,
     storage_slots: Vec<Bytes32>,
+    gas_usages: Vec<GasInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ProfileFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpcodeProfile {
+    pub opcode: String,
+    pub count: u64,
+    pub gas: u64,
+    pub share: f64,
+    pub memory_expansion_gas: u64,
 }
 
 #[derive(Debug)]
@@ -38,11 +61,22 @@ pub struct StorageInfo {
     pub value: Bytes32,
 }
 
+#[derive(Debug, Clone)]
+pub struct GasInfo {
+    pub instruction: InstructionType,
+    pub static_cost: u64,
+    pub dynamic_cost: u64,
+    pub memory_expansion_cost: u64,
+    pub remaining: u64,
+}
+
 #[derive(Debug)]
 pub enum Component {
     Stack(StackInfo),
     Memory(MemoryInfo),
     Storage(StorageInfo),
+    Gas(GasInfo),
+    Log(Log),
 }
 
 impl Registry {
@@ -80,11 +114,11 @@ impl History {
                     format_item_info(info.item_1.unwrap(), info.item_1_index.unwrap())
                 );
 
-                if info.item_2.is_some() {
+                if let Some(item_2) = info.item_2 {
                     description = format!(
                         "{} and {}",
                         description,
-                        format_item_info(info.item_2.unwrap(), info.item_2_index.unwrap())
+                        format_item_info(item_2, info.item_2_index.unwrap())
                     );
                 }
 
@@ -105,6 +139,37 @@ impl History {
                     info.value, info.slot,
                 );
 
+                self.registry.push(Registry::new(description, component)?);
+            }
+            Component::Gas(info) => {
+                let description = format!(
+                    "[GAS]: The opcode {:?} cost {} gas (static: {}, dynamic: {}, memory expansion: {}) leaving {} gas.",
+                    info.instruction,
+                    info.total_cost(),
+                    info.static_cost,
+                    info.dynamic_cost,
+                    info.memory_expansion_cost,
+                    info.remaining,
+                );
+
+                self.registry.push(Registry::new(description, component)?);
+            }
+            Component::Log(log) => {
+                let topics = log
+                    .topics
+                    .iter()
+                    .map(|topic| format!("0x{topic}"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                let description = format!(
+                    "[LOG]: The address {} emitted LOG{} with the topics [{}] and the data 0x{}.",
+                    log.address,
+                    log.topics.len(),
+                    topics,
+                    hex::encode(&log.data),
+                );
+
                 self.registry.push(Registry::new(description, component)?);
             }
         }
@@ -128,6 +193,74 @@ impl History {
         );
     }
 
+    /// Aggregates the saved gas usages per opcode, sorted by total gas in descending order.
+    pub fn profile(&self) -> Vec<OpcodeProfile> {
+        let mut profiles: HashMap<String, OpcodeProfile> = HashMap::new();
+        let total_gas: u64 = self.gas_usages.iter().map(|info| info.total_cost()).sum();
+
+        for info in &self.gas_usages {
+            let opcode = info.instruction.to_string();
+            let profile = profiles
+                .entry(opcode.clone())
+                .or_insert_with(|| OpcodeProfile {
+                    opcode,
+                    count: 0,
+                    gas: 0,
+                    share: 0.0,
+                    memory_expansion_gas: 0,
+                });
+
+            profile.count += 1;
+            profile.gas += info.total_cost();
+            profile.memory_expansion_gas += info.memory_expansion_cost;
+        }
+
+        let mut profiles: Vec<OpcodeProfile> = profiles
+            .into_values()
+            .map(|mut profile| {
+                if total_gas > 0 {
+                    profile.share = profile.gas as f64 * 100.0 / total_gas as f64;
+                }
+                profile
+            })
+            .collect();
+
+        profiles.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| a.opcode.cmp(&b.opcode)));
+
+        profiles
+    }
+
+    pub fn summarize_profile(&self, format: ProfileFormat) -> Result<(), Box<dyn Error>> {
+        let profiles = self.profile();
+
+        match format {
+            ProfileFormat::Text => {
+                println!("Gas Profile:");
+                println!(
+                    "{:<16}{:>8}{:>12}{:>10}{:>12}",
+                    "Opcode", "Count", "Gas", "Share", "Memory"
+                );
+                profiles.iter().for_each(|p| {
+                    println!(
+                        "{:<16}{:>8}{:>12}{:>9.2}%{:>12}",
+                        p.opcode, p.count, p.gas, p.share, p.memory_expansion_gas
+                    );
+                });
+                println!(
+                    "{:<16}{:>8}{:>12}",
+                    "Total",
+                    profiles.iter().map(|p| p.count).sum::<u64>(),
+                    profiles.iter().map(|p| p.gas).sum::<u64>()
+                );
+            }
+            ProfileFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(&profiles)?);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn analyze(&self, vm: &Vm) {
         println!("Stack:");
         println!("{:?}", vm.stack);
@@ -141,6 +274,20 @@ impl History {
             let data = vm.storage.sload(*ss).unwrap();
             println!("Location: 0x{}, Data: 0x{}", ss, data);
         });
+        println!("\nOutput:");
+        println!(
+            "Status: {}, Data: 0x{}",
+            if vm.reverted { "reverted" } else { "success" },
+            hex::encode(&vm.output)
+        );
+        println!("\nGas:");
+        println!(
+            "Limit: {}, Used: {}, Remaining: {}, Refunded: {}",
+            vm.gas.limit(),
+            vm.gas.used(),
+            vm.gas.remaining(),
+            gas::capped_refund(vm.spec, vm.gas.used(), vm.gas.refunded())
+        );
     }
 
     pub fn save_memory_location(&mut self, location: Bytes32) {
@@ -150,6 +297,22 @@ impl History {
     pub fn save_storage_slot(&mut self, slot: Bytes32) {
         self.storage_slots.push(slot);
     }
+
+    pub fn save_gas_usage(&mut self, info: GasInfo) {
+        self.gas_usages.push(info);
+    }
+
+    /// Appends the events of a sub-call frame. Its gas usages are left out, as the
+    /// gas used by the callee is already part of the cost of the call.
+    pub fn extend(&mut self, other: History) {
+        self.registry.extend(other.registry);
+    }
+}
+
+impl GasInfo {
+    pub fn total_cost(&self) -> u64 {
+        self.static_cost + self.dynamic_cost + self.memory_expansion_cost
+    }
 }
 
 impl Component {
@@ -190,10 +353,30 @@ impl Component {
     pub fn build_storage(slot: Bytes32, value: Bytes32) -> Self {
         Component::Storage(StorageInfo { slot, value })
     }
+
+    pub fn build_gas(
+        instruction: InstructionType,
+        static_cost: u64,
+        dynamic_cost: u64,
+        memory_expansion_cost: u64,
+        remaining: u64,
+    ) -> Self {
+        Component::Gas(GasInfo {
+            instruction,
+            static_cost,
+            dynamic_cost,
+            memory_expansion_cost,
+            remaining,
+        })
+    }
 }
 
 #[cfg(test)]
+// NOTE: the original tests compare against bool literals
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
+    use crate::utils::address::Address;
+
     use super::*;
 
     #[test]
@@ -229,8 +412,21 @@ mod tests {
             slot: "01".parse::<Bytes32>()?,
             value: "010203".parse::<Bytes32>()?,
         }))?;
+        history.save_on_event(Component::Gas(GasInfo {
+            instruction: InstructionType::MSTORE,
+            static_cost: 3,
+            dynamic_cost: 0,
+            memory_expansion_cost: 3,
+            remaining: 94,
+        }))?;
+
+        history.save_on_event(Component::Log(Log {
+            address: Address::from(0x1000),
+            topics: vec![Bytes32::from(1)],
+            data: vec![0xaa],
+        }))?;
 
-        assert_eq!(history.registry.len(), 5);
+        assert_eq!(history.registry.len(), 7);
 
         Ok(())
     }
@@ -303,4 +499,55 @@ mod tests {
             assert_eq!(storage_info.value, Bytes32::from(1));
         }
     }
+
+    #[test]
+    fn it_profiles_gas_usages() {
+        let mut history = History::new();
+
+        history.save_gas_usage(GasInfo {
+            instruction: InstructionType::PUSH(1),
+            static_cost: 3,
+            dynamic_cost: 0,
+            memory_expansion_cost: 0,
+            remaining: 97,
+        });
+        history.save_gas_usage(GasInfo {
+            instruction: InstructionType::PUSH(1),
+            static_cost: 3,
+            dynamic_cost: 0,
+            memory_expansion_cost: 0,
+            remaining: 94,
+        });
+        history.save_gas_usage(GasInfo {
+            instruction: InstructionType::MSTORE,
+            static_cost: 3,
+            dynamic_cost: 0,
+            memory_expansion_cost: 15,
+            remaining: 76,
+        });
+
+        let profiles = history.profile();
+
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].opcode, "MSTORE");
+        assert_eq!(profiles[0].count, 1);
+        assert_eq!(profiles[0].gas, 18);
+        assert_eq!(profiles[0].share, 75.0);
+        assert_eq!(profiles[0].memory_expansion_gas, 15);
+        assert_eq!(profiles[1].opcode, "PUSH1");
+        assert_eq!(profiles[1].count, 2);
+        assert_eq!(profiles[1].gas, 6);
+    }
+
+    #[test]
+    fn test_build_gas() {
+        let gas_component = Component::build_gas(InstructionType::KECCAK256, 30, 6, 0, 64);
+
+        if let Component::Gas(gas_info) = gas_component {
+            assert_eq!(gas_info.static_cost, 30);
+            assert_eq!(gas_info.dynamic_cost, 6);
+            assert_eq!(gas_info.memory_expansion_cost, 0);
+            assert_eq!(gas_info.remaining, 64);
+        }
+    }
 }
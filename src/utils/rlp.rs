@@ -0,0 +1,301 @@
+use super::{address::Address, errors::RlpError};
+
+/// Maximum nesting of decoded lists, far deeper than any transaction needs.
+pub const MAX_DEPTH: usize = 32;
+
+/// An RLP item, which is either a byte string or a list of items.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RlpItem {
+    Bytes(Vec<u8>),
+    List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            RlpItem::Bytes(bytes) if bytes.len() == 1 && bytes[0] < 0x80 => bytes.clone(),
+            RlpItem::Bytes(bytes) => {
+                let mut encoded = encode_length(bytes.len(), 0x80);
+                encoded.extend_from_slice(bytes);
+
+                encoded
+            }
+            RlpItem::List(items) => {
+                let payload: Vec<u8> = items.iter().flat_map(|item| item.encode()).collect();
+
+                let mut encoded = encode_length(payload.len(), 0xc0);
+                encoded.extend(payload);
+
+                encoded
+            }
+        }
+    }
+
+    /// Decodes a single item which has to span the whole input.
+    pub fn decode(data: &[u8]) -> Result<RlpItem, RlpError> {
+        let (item, rest) = decode_item(data, 0)?;
+
+        if !rest.is_empty() {
+            return Err(RlpError::TrailingBytes);
+        }
+
+        Ok(item)
+    }
+
+    pub fn as_bytes(&self) -> Result<&[u8], RlpError> {
+        match self {
+            RlpItem::Bytes(bytes) => Ok(bytes),
+            RlpItem::List(_) => Err(RlpError::ExpectedBytes),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&[RlpItem], RlpError> {
+        match self {
+            RlpItem::List(items) => Ok(items),
+            RlpItem::Bytes(_) => Err(RlpError::ExpectedList),
+        }
+    }
+
+    /// Integers are big-endian bytes without leading zeroes, so zero is the empty string.
+    pub fn as_u128(&self) -> Result<u128, RlpError> {
+        let bytes = self.as_bytes()?;
+
+        if bytes.first() == Some(&0) {
+            return Err(RlpError::NonCanonical);
+        }
+        if bytes.len() > 16 {
+            return Err(RlpError::IntegerOverflow);
+        }
+
+        Ok(bytes
+            .iter()
+            .fold(0, |value, byte| (value << 8) | *byte as u128))
+    }
+
+    pub fn as_u64(&self) -> Result<u64, RlpError> {
+        u64::try_from(self.as_u128()?).map_err(|_| RlpError::IntegerOverflow)
+    }
+}
+
+impl From<Vec<u8>> for RlpItem {
+    fn from(value: Vec<u8>) -> Self {
+        RlpItem::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for RlpItem {
+    fn from(value: &[u8]) -> Self {
+        RlpItem::Bytes(value.to_vec())
+    }
+}
+
+impl From<Address> for RlpItem {
+    fn from(value: Address) -> Self {
+        RlpItem::Bytes(value.0.to_vec())
+    }
+}
+
+/// Integers are encoded as big-endian bytes without leading zeroes.
+impl From<u128> for RlpItem {
+    fn from(value: u128) -> Self {
+        RlpItem::Bytes(trim_leading_zeroes(&value.to_be_bytes()))
+    }
+}
+
+impl From<u64> for RlpItem {
+    fn from(value: u64) -> Self {
+        RlpItem::from(value as u128)
+    }
+}
+
+impl From<Vec<RlpItem>> for RlpItem {
+    fn from(value: Vec<RlpItem>) -> Self {
+        RlpItem::List(value)
+    }
+}
+
+fn encode_length(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+
+    let length = trim_leading_zeroes(&length.to_be_bytes());
+
+    let mut encoded = vec![offset + 55 + length.len() as u8];
+    encoded.extend(length);
+
+    encoded
+}
+
+/// Splits the next item off the input and returns it along with the remaining bytes.
+fn decode_item(data: &[u8], depth: usize) -> Result<(RlpItem, &[u8]), RlpError> {
+    let prefix = *data.first().ok_or(RlpError::UnexpectedEnd)?;
+
+    match prefix {
+        0x00..=0x7f => Ok((RlpItem::Bytes(vec![prefix]), &data[1..])),
+        0x80..=0xbf => {
+            let (payload, rest) = split_payload(data, 0x80)?;
+
+            // NOTE: a single byte below 0x80 is its own encoding
+            if payload.len() == 1 && payload[0] < 0x80 {
+                return Err(RlpError::NonCanonical);
+            }
+
+            Ok((RlpItem::Bytes(payload.to_vec()), rest))
+        }
+        _ => {
+            if depth == MAX_DEPTH {
+                return Err(RlpError::TooDeep);
+            }
+
+            let (mut payload, rest) = split_payload(data, 0xc0)?;
+
+            let mut items = vec![];
+            while !payload.is_empty() {
+                let (item, remaining) = decode_item(payload, depth + 1)?;
+                items.push(item);
+                payload = remaining;
+            }
+
+            Ok((RlpItem::List(items), rest))
+        }
+    }
+}
+
+/// Reads the length encoded by the prefix and splits the payload off the input.
+fn split_payload(data: &[u8], offset: u8) -> Result<(&[u8], &[u8]), RlpError> {
+    let prefix = (data[0] - offset) as usize;
+
+    let (length, start) = if prefix < 56 {
+        (prefix, 1)
+    } else {
+        let size = prefix - 55;
+        let bytes = data.get(1..1 + size).ok_or(RlpError::UnexpectedEnd)?;
+
+        if bytes[0] == 0 {
+            return Err(RlpError::NonCanonical);
+        }
+        if size > std::mem::size_of::<usize>() {
+            return Err(RlpError::IntegerOverflow);
+        }
+
+        let length = bytes
+            .iter()
+            .fold(0, |length, byte| (length << 8) | *byte as usize);
+
+        if length < 56 {
+            return Err(RlpError::NonCanonical);
+        }
+
+        (length, 1 + size)
+    };
+
+    let end = start.checked_add(length).ok_or(RlpError::UnexpectedEnd)?;
+    let payload = data.get(start..end).ok_or(RlpError::UnexpectedEnd)?;
+
+    Ok((payload, &data[end..]))
+}
+
+fn trim_leading_zeroes(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .copied()
+        .skip_while(|byte| *byte == 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_encodes_bytes() {
+        assert_eq!(RlpItem::from(b"dog".as_slice()).encode(), b"\x83dog");
+        assert_eq!(RlpItem::Bytes(vec![]).encode(), vec![0x80]);
+        assert_eq!(RlpItem::from(vec![0x0f]).encode(), vec![0x0f]);
+        assert_eq!(RlpItem::from(vec![0x80]).encode(), vec![0x81, 0x80]);
+
+        let long = vec![0xaa; 56];
+        let encoded = RlpItem::from(long.clone()).encode();
+
+        assert_eq!(encoded[..2], [0xb8, 56]);
+        assert_eq!(encoded[2..], long);
+    }
+
+    #[test]
+    fn it_encodes_integers() {
+        assert_eq!(RlpItem::from(0u64).encode(), vec![0x80]);
+        assert_eq!(RlpItem::from(15u64).encode(), vec![0x0f]);
+        assert_eq!(RlpItem::from(1024u64).encode(), vec![0x82, 0x04, 0x00]);
+    }
+
+    #[test]
+    fn it_encodes_lists() {
+        let list = RlpItem::from(vec![
+            RlpItem::from(b"cat".as_slice()),
+            RlpItem::from(b"dog".as_slice()),
+        ]);
+
+        assert_eq!(list.encode(), b"\xc8\x83cat\x83dog");
+        assert_eq!(RlpItem::List(vec![]).encode(), vec![0xc0]);
+    }
+
+    #[test]
+    fn it_decodes_items() -> Result<(), RlpError> {
+        assert_eq!(
+            RlpItem::decode(b"\x83dog")?,
+            RlpItem::from(b"dog".as_slice())
+        );
+        assert_eq!(RlpItem::decode(&[0x0f])?, RlpItem::from(vec![0x0f]));
+        assert_eq!(RlpItem::decode(&[0x80])?.as_u64()?, 0);
+        assert_eq!(RlpItem::decode(&[0x82, 0x04, 0x00])?.as_u64()?, 1024);
+
+        let list = RlpItem::from(vec![
+            RlpItem::from(b"cat".as_slice()),
+            RlpItem::from(vec![RlpItem::from(vec![0xaa; 56])]),
+            RlpItem::List(vec![]),
+        ]);
+
+        assert_eq!(RlpItem::decode(&list.encode())?, list);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_input_returns_rlp_error() {
+        assert_eq!(RlpItem::decode(&[]), Err(RlpError::UnexpectedEnd));
+        assert_eq!(RlpItem::decode(&[0x83, b'd']), Err(RlpError::UnexpectedEnd));
+        assert_eq!(RlpItem::decode(&[0x0f, 0x0f]), Err(RlpError::TrailingBytes));
+        assert_eq!(RlpItem::decode(&[0x81, 0x0f]), Err(RlpError::NonCanonical));
+        assert_eq!(
+            RlpItem::decode(&[0xb8, 0x01, 0xaa]),
+            Err(RlpError::NonCanonical)
+        );
+        assert_eq!(
+            RlpItem::decode(&[0x82, 0x00, 0x01]).and_then(|item| item.as_u64()),
+            Err(RlpError::NonCanonical)
+        );
+        assert_eq!(
+            RlpItem::from(vec![0x01; 17]).as_u128(),
+            Err(RlpError::IntegerOverflow)
+        );
+
+        // NOTE: lists nested deeper than the limit
+        let mut nested = RlpItem::List(vec![]);
+        for _ in 0..MAX_DEPTH {
+            nested = RlpItem::List(vec![nested]);
+        }
+
+        assert_eq!(RlpItem::decode(&nested.encode()), Err(RlpError::TooDeep));
+
+        let RlpItem::List(items) = nested else {
+            unreachable!()
+        };
+
+        assert!(RlpItem::decode(&items[0].encode()).is_ok());
+        assert_eq!(
+            RlpItem::decode(&[0xc0]).and_then(|item| item.as_u64()),
+            Err(RlpError::ExpectedBytes)
+        );
+    }
+}
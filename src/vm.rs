@@ -1,20 +1,60 @@
-use std::{any::Any, error::Error, fmt::Write, str::FromStr};
+use std::{any::Any, error::Error, fmt::Write, mem, panic, str::FromStr, thread};
 
 use tiny_keccak::{Hasher, Keccak};
 
 use crate::{
+    env::{BlockEnv, TxEnv},
+    eof::{self, EofContainer},
+    gas::{self, Gas},
     instruction::InstructionType,
     memory::Memory,
-    stack::Stack,
+    precompile::{self, Precompile},
+    spec::SpecId,
+    stack::{Stack, STACK_SIZE_LIMIT},
+    state::WorldState,
     storage::Storage,
     utils::{
+        address::Address,
         bytes32::{Bytes32, Pow},
-        errors::VmError,
-        history::{Component, History},
+        crypto,
+        errors::{GasError, InstructionError, StackError, VmError},
+        history::{Component, GasInfo, History, ProfileFormat},
     },
     Lexer,
 };
 
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<Bytes32>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+pub struct ExecutionResult {
+    pub logs: Vec<Log>,
+    pub gas_used: u64,
+    /// Gas to refund, capped by `gas::capped_refund` in the outermost frame.
+    /// The net refund of a nested frame can be negative (EIP-2200).
+    pub gas_refunded: i64,
+    pub output: Vec<u8>,
+    pub reverted: bool,
+}
+
+/// Maximum depth of nested message calls.
+pub const CALL_DEPTH_LIMIT: usize = 1024;
+/// Stack size of the thread running nested message calls, which fits frames up to
+/// `CALL_DEPTH_LIMIT` in debug builds.
+pub const CALL_STACK_SIZE: usize = 256 * 1024 * 1024;
+/// Maximum depth of nested EOF function calls (EIP-4750).
+pub const RETURN_STACK_LIMIT: usize = 1024;
+/// Maximum size of deployed code (EIP-170).
+pub const MAX_CODE_SIZE: usize = 0x6000;
+/// Maximum size of init code (EIP-3860).
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+/// New code starting with this byte is rejected (EIP-3541).
+pub const EOF_MAGIC: u8 = 0xef;
+
 #[derive(Default, Debug)]
 pub struct Vm<'a> {
     pub stack: Stack<String>,
@@ -22,728 +62,2791 @@ pub struct Vm<'a> {
     pub memory: Memory,
     pub storage: Storage,
     pub history: History,
+    pub gas: Gas,
     pub verbose: bool,
+    pub profile: Option<ProfileFormat>,
+    pub calldata: Vec<u8>,
+    pub code: Vec<u8>,
+    pub block: BlockEnv,
+    pub tx: TxEnv,
+    pub logs: Vec<Log>,
+    pub state: WorldState,
+    pub output: Vec<u8>,
+    pub reverted: bool,
+    pub return_data: Vec<u8>,
+    pub depth: usize,
+    pub is_static: bool,
+    pub spec: SpecId,
+    pub eof: Option<EofContainer>,
+    code_section: usize,
+    return_stack: Vec<(usize, usize)>,
+    halted: bool,
 }
 
 impl<'a> Vm<'a> {
     pub fn new(bytecode: &'a str, verbose: bool) -> Result<Self, Box<dyn Error>> {
+        let lexer = Lexer::new(bytecode)?;
+        let code = hex::decode(lexer.bytecode)?;
+
         Ok(Self {
-            lexer: Lexer::new(bytecode)?,
+            lexer,
+            code,
             verbose,
             ..Default::default()
         })
     }
 
-    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
-        self.lexer.read_char();
-
-        'main: while self.lexer.ch != '\0' {
-            let instruction = self.lexer.next_byte()?;
-            let instruction = InstructionType::from_str(&instruction)?;
-
-            let mut build_initials = || -> Result<Box<dyn Any>, Box<dyn Error>> {
-                match instruction {
-                    InstructionType::ISZERO
-                    | InstructionType::NOT
-                    | InstructionType::KECCAK256
-                    | InstructionType::POP
-                    | InstructionType::MLOAD
-                    | InstructionType::SLOAD => {
-                        let (index_1, item_1) = self.pop_first_item(instruction.clone())?;
-
-                        if self.verbose {
-                            self.history
-                                .save_on_event(Component::build_stack_with_one_item(
-                                    instruction.clone(),
-                                    item_1,
-                                    index_1 as u16,
-                                ))?;
-                        }
+    pub fn run(&mut self) -> Result<ExecutionResult, Box<dyn Error>> {
+        while self.step()? {}
+
+        Ok(ExecutionResult {
+            // NOTE: logs of a reverted frame are discarded along with its state changes
+            logs: if self.reverted {
+                vec![]
+            } else {
+                self.logs.clone()
+            },
+            gas_used: self.gas.used(),
+            gas_refunded: if self.depth == 0 {
+                gas::capped_refund(self.spec, self.gas.used(), self.gas.refunded()) as i64
+            } else {
+                self.gas.refunded()
+            },
+            output: self.output.clone(),
+            reverted: self.reverted,
+        })
+    }
+
+    /// Executes a single instruction and returns whether there is anything left to execute.
+    pub fn step(&mut self) -> Result<bool, Box<dyn Error>> {
+        if self.lexer.read_position == 0 {
+            self.lexer.read_char();
+            self.load_storage();
+
+            // NOTE: EOF containers are decoded and validated once, then executed in place
+            if self.spec.is_enabled_in(SpecId::Prague) && eof::is_eof(&self.code) {
+                let container = EofContainer::parse(&self.code)?;
+
+                self.lexer.seek(container.code_offsets[0]);
+                self.eof = Some(container);
+            }
+
+            // NOTE: the accounts of the transaction itself are warm from the start (EIP-2929)
+            if self.depth == 0 {
+                self.state.warm_address(self.tx.address);
+                self.state.warm_address(self.tx.origin);
+                self.state.warm_address(self.tx.caller);
+
+                // NOTE: the coinbase is warm since Shanghai (EIP-3651)
+                if self.spec.is_enabled_in(SpecId::Shanghai) {
+                    self.state.warm_address(self.block.coinbase);
+                }
+
+                for address in precompile::addresses(self.spec) {
+                    self.state.warm_address(address);
+                }
+            }
+        }
+
+        if self.halted || self.lexer.ch == '\0' {
+            return Ok(false);
+        }
+
+        let running = self.execute()?;
+
+        if !running {
+            self.halted = true;
+
+            if !self.reverted {
+                self.save_storage();
+
+                if self.depth == 0 {
+                    self.state.finalize();
+                }
+            }
+        }
+
+        Ok(running)
+    }
+
+    pub fn gas_left(&self) -> u64 {
+        self.gas.remaining()
+    }
+
+    /// Checks out the storage of the executing account from the world state.
+    fn load_storage(&mut self) {
+        if let Some(account) = self.state.account(&self.tx.address) {
+            self.storage = account.storage.clone();
+        }
+    }
+
+    /// Writes the storage of the executing account back to the world state.
+    fn save_storage(&mut self) {
+        if !self.storage.is_empty() || self.state.exists(&self.tx.address) {
+            self.state.account_mut(self.tx.address).storage = self.storage.clone();
+        }
+    }
+
+    fn execute(&mut self) -> Result<bool, Box<dyn Error>> {
+        let opcode = self.lexer.next_byte()?;
+        let instruction = InstructionType::from_str(&opcode)?;
 
-                        Ok(Box::new(item_1))
+        if instruction.is_eof_only() && self.eof.is_none() {
+            let mut array = [0; 2];
+            array.copy_from_slice(opcode.as_bytes());
+
+            return Err(Box::new(InstructionError::InvalidInstruction(array)));
+        }
+
+        if !instruction.is_enabled_in(self.spec) {
+            return Err(Box::new(VmError::NotActivated(instruction, self.spec)));
+        }
+
+        let static_cost = gas::static_cost(&instruction, self.spec);
+        self.gas.charge(static_cost)?;
+        let mut dynamic_cost = 0;
+
+        let mut build_initials = || -> Result<Box<dyn Any>, Box<dyn Error>> {
+            match instruction {
+                InstructionType::ISZERO
+                | InstructionType::NOT
+                | InstructionType::KECCAK256
+                | InstructionType::POP
+                | InstructionType::MLOAD
+                | InstructionType::SLOAD
+                | InstructionType::CALLDATALOAD
+                | InstructionType::BALANCE
+                | InstructionType::EXTCODESIZE
+                | InstructionType::EXTCODEHASH
+                | InstructionType::BLOCKHASH
+                | InstructionType::RJUMPI => {
+                    let (index_1, item_1) = self.pop_first_item(instruction.clone())?;
+
+                    if self.verbose {
+                        self.history
+                            .save_on_event(Component::build_stack_with_one_item(
+                                instruction.clone(),
+                                item_1,
+                                index_1 as u16,
+                            ))?;
                     }
-                    InstructionType::PUSH(size)
-                    | InstructionType::DUP(size)
-                    | InstructionType::SWAP(size) => {
-                        if matches!(instruction, InstructionType::PUSH(_)) {
-                            let mut counter = 0;
-                            let mut data = "".to_string();
-                            while counter < size {
-                                data += &self.lexer.next_byte()?;
-
-                                counter += 1;
-                            }
 
-                            let index = self.stack.push(data.clone())?;
-
-                            self.history
-                                .save_on_event(Component::build_stack_with_one_item(
-                                    instruction.clone(),
-                                    data.parse::<Bytes32>()?,
-                                    index as u16,
-                                ))?;
-                        } else if matches!(instruction, InstructionType::DUP(_)) {
-                            let (index, item) = self.stack.dup(size as usize)?;
-
-                            self.history
-                                .save_on_event(Component::build_stack_with_one_item(
-                                    instruction.clone(),
-                                    item.parse::<Bytes32>()?,
-                                    index as u16,
-                                ))?;
-                        } else {
-                            unsafe {
-                                let ([index_1, index_2], [item_1, item_2]) =
-                                    self.stack.swap(size as usize)?;
-
-                                self.history.save_on_event(Component::build_stack(
-                                    instruction.clone(),
-                                    item_1.parse::<Bytes32>()?,
-                                    index_1 as u16,
-                                    item_2.parse::<Bytes32>()?,
-                                    index_2 as u16,
-                                ))?;
-                            }
+                    Ok(Box::new(item_1))
+                }
+                InstructionType::PUSH(size)
+                | InstructionType::DUP(size)
+                | InstructionType::SWAP(size) => {
+                    if matches!(instruction, InstructionType::PUSH(_)) {
+                        let mut counter = 0;
+                        let mut data = "".to_string();
+                        while counter < size {
+                            data += &self.lexer.next_byte()?;
+
+                            counter += 1;
                         }
 
-                        Ok(Box::new(0))
-                    }
-                    _ => {
-                        let ([index_1, index_2], [item_1, item_2]) =
-                            self.pop_first_two_items(instruction.clone())?;
+                        let index = self.stack.push(data.clone())?;
+
+                        self.history
+                            .save_on_event(Component::build_stack_with_one_item(
+                                instruction.clone(),
+                                data.parse::<Bytes32>()?,
+                                index as u16,
+                            ))?;
+                    } else if matches!(instruction, InstructionType::DUP(_)) {
+                        let (index, item) = self.stack.dup(size as usize)?;
+
+                        self.history
+                            .save_on_event(Component::build_stack_with_one_item(
+                                instruction.clone(),
+                                item.parse::<Bytes32>()?,
+                                index as u16,
+                            ))?;
+                    } else {
+                        unsafe {
+                            let ([index_1, index_2], [item_1, item_2]) =
+                                self.stack.swap(size as usize)?;
 
-                        if self.verbose {
                             self.history.save_on_event(Component::build_stack(
                                 instruction.clone(),
-                                item_1,
+                                item_1.parse::<Bytes32>()?,
                                 index_1 as u16,
-                                item_2,
+                                item_2.parse::<Bytes32>()?,
                                 index_2 as u16,
                             ))?;
+                        }
+                    }
 
-                            match instruction {
-                                InstructionType::MSTORE => {
-                                    self.history.save_memory_location(item_1);
-                                    self.history
-                                        .save_on_event(Component::build_memory(item_1, item_2))?
-                                }
-                                InstructionType::SSTORE => {
-                                    self.history.save_storage_slot(item_1);
-                                    self.history
-                                        .save_on_event(Component::build_storage(item_1, item_2))?
-                                }
-                                _ => {}
+                    Ok(Box::new(0))
+                }
+                _ => {
+                    let ([index_1, index_2], [item_1, item_2]) =
+                        self.pop_first_two_items(instruction.clone())?;
+
+                    if self.verbose {
+                        self.history.save_on_event(Component::build_stack(
+                            instruction.clone(),
+                            item_1,
+                            index_1 as u16,
+                            item_2,
+                            index_2 as u16,
+                        ))?;
+
+                        match instruction {
+                            InstructionType::MSTORE => {
+                                self.history.save_memory_location(item_1);
+                                self.history
+                                    .save_on_event(Component::build_memory(item_1, item_2))?
                             }
+                            InstructionType::SSTORE => {
+                                self.history.save_storage_slot(item_1);
+                                self.history
+                                    .save_on_event(Component::build_storage(item_1, item_2))?
+                            }
+                            _ => {}
                         }
-
-                        Ok(Box::new((item_1, item_2)))
                     }
+
+                    Ok(Box::new((item_1, item_2)))
                 }
-            };
+            }
+        };
 
-            match instruction {
-                InstructionType::STOP => break 'main,
-                InstructionType::ADD => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 + item_2;
+        match instruction {
+            InstructionType::STOP => {
+                self.charge_dynamic_gas(instruction, static_cost, dynamic_cost)?;
+                self.halted = true;
 
-                    self.stack.push(result.parse_and_trim()?)?;
-                }
-                InstructionType::MUL => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 * item_2;
+                return Ok(false);
+            }
+            InstructionType::ADD => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 + item_2;
 
-                    self.stack.push(result.parse_and_trim()?)?;
-                }
-                InstructionType::SUB => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 - item_2;
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::MUL => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 * item_2;
 
-                    self.stack.push(result.parse_and_trim()?)?;
-                }
-                InstructionType::DIV => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 / item_2;
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::SUB => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 - item_2;
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::DIV => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 / item_2;
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::MOD => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 % item_2;
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::EXP => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1.pow(item_2);
+                dynamic_cost = gas::exp_cost(item_2, self.spec);
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::LT => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 < item_2;
+
+                self.stack.push(format!("{:x}", result as u128))?;
+            }
+            InstructionType::GT => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 > item_2;
+
+                self.stack.push(format!("{:x}", result as u128))?;
+            }
+            InstructionType::EQ => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 == item_2;
+
+                self.stack.push(format!("{:x}", result as u128))?;
+            }
+            InstructionType::ISZERO => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+                let result = item_1 == Bytes32::from(0);
+
+                self.stack.push(format!("{:x}", result as u128))?;
+            }
+            InstructionType::AND => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 & item_2;
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::OR => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 | item_2;
 
-                    self.stack.push(result.parse_and_trim()?)?;
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::XOR => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                let result = item_1 ^ item_2;
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::NOT => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+                let result = !item_1;
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::BYTE => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+
+                let result = if item_1 < Bytes32::from(32) {
+                    (item_2 >> (Bytes32::from(8) * (Bytes32::from(31) - item_1)))
+                        & Bytes32::from(0xFF)
+                } else {
+                    Bytes32::from(0)
+                };
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::KECCAK256 => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                // NOTE: need to trim leading zeroes for keccak operation
+                let mut data: Vec<u8> = vec![];
+                for &byte in item_1.0.iter() {
+                    if byte != 0 || !data.is_empty() {
+                        data.push(byte);
+                    }
                 }
-                InstructionType::MOD => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 % item_2;
 
-                    self.stack.push(result.parse_and_trim()?)?;
+                dynamic_cost = gas::keccak256_cost(data.len() as u64);
+
+                let mut result = [0u8; 32];
+                let mut keccak = Keccak::v256();
+                keccak.update(&data);
+                keccak.finalize(&mut result);
+
+                let mut hex_result = String::with_capacity(result.len() * 2);
+                for byte in &result {
+                    write!(&mut hex_result, "{:02x}", byte)?;
                 }
-                InstructionType::EXP => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1.pow(item_2);
 
-                    self.stack.push(result.parse_and_trim()?)?;
+                self.stack.push(hex_result)?;
+            }
+            InstructionType::POP => {
+                build_initials()?.downcast::<Bytes32>().unwrap();
+            }
+            InstructionType::MLOAD => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                self.check_memory_expansion(item_1.saturating_to_usize(), 32)?;
+
+                let result;
+                unsafe {
+                    result = self.memory.mload(item_1)?;
                 }
-                InstructionType::LT => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 < item_2;
+                let result: String = result.try_into()?;
 
-                    self.stack.push(format!("{:x}", result as u128))?;
+                self.stack.push(result)?;
+            }
+            InstructionType::MSTORE => unsafe {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+
+                self.check_memory_expansion(item_1.saturating_to_usize(), 32)?;
+                self.memory.mstore(item_1, item_2)?;
+            },
+            InstructionType::SLOAD => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                if self.state.warm_slot(self.tx.address, item_1)
+                    && self.spec.is_enabled_in(SpecId::Berlin)
+                {
+                    dynamic_cost = gas::cold_sload_cost(true);
                 }
-                InstructionType::GT => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 > item_2;
 
-                    self.stack.push(format!("{:x}", result as u128))?;
+                let result = self
+                    .storage
+                    .sload(item_1)
+                    .copied()
+                    .unwrap_or(Bytes32::from(0));
+                let result: String = result.to_string();
+
+                self.stack.push(result)?;
+            }
+            InstructionType::SSTORE => {
+                let (item_1, item_2) = *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+
+                if self.is_static {
+                    return Err(Box::new(VmError::WriteProtection(instruction)));
                 }
-                InstructionType::EQ => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 == item_2;
 
-                    self.stack.push(format!("{:x}", result as u128))?;
+                // NOTE: fails when not more than the call stipend is left (EIP-2200)
+                if self.spec.is_enabled_in(SpecId::Istanbul)
+                    && self.gas.remaining() <= gas::SSTORE_SENTRY_GAS
+                {
+                    return Err(Box::new(GasError::OutOfGas));
                 }
-                InstructionType::ISZERO => {
-                    let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
-                    let result = item_1 == Bytes32::from(0);
 
-                    self.stack.push(format!("{:x}", result as u128))?;
+                let current = self
+                    .storage
+                    .sload(item_1)
+                    .copied()
+                    .unwrap_or(Bytes32::from(0));
+                let original = self.state.original_slot(self.tx.address, item_1, current);
+                dynamic_cost = gas::sstore_cost(original, current, item_2, self.spec);
+                self.gas
+                    .refund(gas::sstore_refund(original, current, item_2, self.spec));
+
+                // NOTE: writing a cold slot pays the full cold load on top (EIP-2929)
+                if self.state.warm_slot(self.tx.address, item_1)
+                    && self.spec.is_enabled_in(SpecId::Berlin)
+                {
+                    dynamic_cost += gas::COLD_SLOAD_GAS;
                 }
-                InstructionType::AND => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 & item_2;
 
-                    self.stack.push(result.parse_and_trim()?)?;
+                self.storage.sstore(item_1, item_2);
+            }
+            InstructionType::CALLDATALOAD => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                let data = read_padded(&self.calldata, item_1.saturating_to_usize(), 32);
+                let result = Bytes32(data.try_into().unwrap());
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::CALLDATASIZE => {
+                self.push_item(&instruction, Bytes32::from(self.calldata.len()))?;
+            }
+            InstructionType::CALLDATACOPY => {
+                let calldata = mem::take(&mut self.calldata);
+                let result = self.copy_to_memory(&instruction, &calldata);
+                self.calldata = calldata;
+
+                dynamic_cost = result?;
+            }
+            InstructionType::CODESIZE => {
+                self.push_item(&instruction, Bytes32::from(self.code.len()))?;
+            }
+            InstructionType::CODECOPY => {
+                let code = mem::take(&mut self.code);
+                let result = self.copy_to_memory(&instruction, &code);
+                self.code = code;
+
+                dynamic_cost = result?;
+            }
+            InstructionType::EXTCODESIZE => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                let address = Address::from(item_1);
+                dynamic_cost = self.account_access_cost(address);
+
+                self.stack
+                    .push(Bytes32::from(self.state.code(&address).len()).parse_and_trim()?)?;
+            }
+            InstructionType::EXTCODECOPY => {
+                let (_, [address]) = self.pop_items::<1>(instruction.clone())?;
+
+                let address = Address::from(address);
+                let code = self.state.code(&address).to_vec();
+
+                dynamic_cost = self.account_access_cost(address);
+                dynamic_cost += self.copy_to_memory(&instruction, &code)?;
+            }
+            InstructionType::EXTCODEHASH => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                let address = Address::from(item_1);
+                dynamic_cost = self.account_access_cost(address);
+
+                self.stack
+                    .push(self.state.code_hash(&address).parse_and_trim()?)?;
+            }
+            InstructionType::RETURNDATASIZE => {
+                self.push_item(&instruction, Bytes32::from(self.return_data.len()))?;
+            }
+            InstructionType::RETURNDATACOPY => {
+                let (_, [memory_offset, offset, size]) =
+                    self.pop_items::<3>(instruction.clone())?;
+
+                let offset = offset.saturating_to_usize();
+                let size = size.saturating_to_usize();
+
+                if offset.saturating_add(size) > self.return_data.len() {
+                    return Err(Box::new(VmError::ReturnDataOutOfBounds));
                 }
-                InstructionType::OR => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 | item_2;
 
-                    self.stack.push(result.parse_and_trim()?)?;
+                let return_data = mem::take(&mut self.return_data);
+                let result = self.store_padded(
+                    &return_data,
+                    memory_offset.saturating_to_usize(),
+                    offset,
+                    size,
+                );
+                self.return_data = return_data;
+
+                dynamic_cost = result?;
+            }
+            InstructionType::ADDRESS => {
+                self.push_item(&instruction, Bytes32::from(self.tx.address))?;
+            }
+            InstructionType::BALANCE => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                let address = Address::from(item_1);
+                dynamic_cost = self.account_access_cost(address);
+
+                self.stack
+                    .push(Bytes32::from(self.state.balance(&address)).parse_and_trim()?)?;
+            }
+            InstructionType::ORIGIN => {
+                self.push_item(&instruction, Bytes32::from(self.tx.origin))?;
+            }
+            InstructionType::CALLER => {
+                self.push_item(&instruction, Bytes32::from(self.tx.caller))?;
+            }
+            InstructionType::CALLVALUE => {
+                self.push_item(&instruction, Bytes32::from(self.tx.value))?;
+            }
+            InstructionType::GASPRICE => {
+                self.push_item(&instruction, Bytes32::from(self.tx.gas_price))?;
+            }
+            InstructionType::BLOCKHASH => {
+                let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+
+                let number = u64::try_from(item_1.saturating_to_usize()).unwrap_or(u64::MAX);
+                let result = self.block.block_hash(number);
+
+                self.stack.push(result.parse_and_trim()?)?;
+            }
+            InstructionType::COINBASE => {
+                self.push_item(&instruction, Bytes32::from(self.block.coinbase))?;
+            }
+            InstructionType::TIMESTAMP => {
+                self.push_item(&instruction, Bytes32::from(self.block.timestamp as u128))?;
+            }
+            InstructionType::NUMBER => {
+                self.push_item(&instruction, Bytes32::from(self.block.number as u128))?;
+            }
+            InstructionType::PREVRANDAO => {
+                self.push_item(&instruction, self.block.prevrandao)?;
+            }
+            InstructionType::GASLIMIT => {
+                self.push_item(&instruction, Bytes32::from(self.block.gas_limit as u128))?;
+            }
+            InstructionType::CHAINID => {
+                self.push_item(&instruction, Bytes32::from(self.block.chain_id as u128))?;
+            }
+            InstructionType::SELFBALANCE => {
+                self.push_item(
+                    &instruction,
+                    Bytes32::from(self.state.balance(&self.tx.address)),
+                )?;
+            }
+            InstructionType::BASEFEE => {
+                self.push_item(&instruction, Bytes32::from(self.block.basefee))?;
+            }
+            InstructionType::GAS => {
+                self.push_item(&instruction, Bytes32::from(self.gas.remaining() as u128))?;
+            }
+            InstructionType::PUSH(size) => {
+                if size > 32 {
+                    return Err(Box::new(VmError::IncompatibleSize(InstructionType::PUSH(
+                        size,
+                    ))));
                 }
-                InstructionType::XOR => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
-                    let result = item_1 ^ item_2;
 
-                    self.stack.push(result.parse_and_trim()?)?;
+                if size == 0 {
+                    self.stack.push("0".to_string())?;
+                } else {
+                    build_initials()?;
+                }
+            }
+            InstructionType::LOG(topics) => {
+                if topics > 4 {
+                    return Err(Box::new(VmError::IncompatibleSize(InstructionType::LOG(
+                        topics,
+                    ))));
                 }
-                InstructionType::NOT => {
-                    let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
-                    let result = !item_1;
 
-                    self.stack.push(result.parse_and_trim()?)?;
+                if self.is_static {
+                    return Err(Box::new(VmError::WriteProtection(instruction)));
                 }
-                InstructionType::BYTE => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
 
-                    let result = if item_1 < Bytes32::from(32) {
-                        (item_2 >> (Bytes32::from(8) * (Bytes32::from(31) - item_1)))
-                            & Bytes32::from(0xFF)
-                    } else {
-                        Bytes32::from(0)
-                    };
+                let (_, [offset, size]) = self.pop_items::<2>(instruction.clone())?;
+                let (_, topics) = self.pop_items_dynamic(instruction.clone(), topics as usize)?;
 
-                    self.stack.push(result.parse_and_trim()?)?;
-                }
-                InstructionType::KECCAK256 => {
-                    let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
-
-                    // NOTE: need to trim leading zeroes for keccak operation
-                    let mut data: Vec<u8> = vec![];
-                    for &byte in item_1.0.iter() {
-                        if byte != 0 || !data.is_empty() {
-                            data.push(byte);
-                        }
-                    }
+                let offset = offset.saturating_to_usize();
+                let size = size.saturating_to_usize();
 
-                    let mut result = [0u8; 32];
-                    let mut keccak = Keccak::v256();
-                    keccak.update(&data);
-                    keccak.finalize(&mut result);
+                self.check_memory_expansion(offset, size)?;
 
-                    let mut hex_result = String::with_capacity(result.len() * 2);
-                    for byte in &result {
-                        write!(&mut hex_result, "{:02x}", byte)?;
-                    }
+                let log = Log {
+                    address: self.tx.address,
+                    topics,
+                    data: self.memory.load_bytes(offset, size),
+                };
+                dynamic_cost = gas::log_cost(size as u64);
 
-                    self.stack.push(hex_result)?;
+                if self.verbose {
+                    self.history.save_on_event(Component::Log(log.clone()))?;
                 }
-                InstructionType::POP => {
-                    build_initials()?.downcast::<Bytes32>().unwrap();
+
+                self.logs.push(log);
+            }
+            InstructionType::CALL
+            | InstructionType::CALLCODE
+            | InstructionType::DELEGATECALL
+            | InstructionType::STATICCALL => {
+                dynamic_cost = self.call(&instruction)?;
+            }
+            InstructionType::CREATE | InstructionType::CREATE2 => {
+                dynamic_cost = self.create(&instruction)?;
+            }
+            InstructionType::SELFDESTRUCT => {
+                if self.is_static {
+                    return Err(Box::new(VmError::WriteProtection(instruction)));
                 }
-                InstructionType::MLOAD => {
-                    let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
 
-                    let result;
-                    unsafe {
-                        result = self.memory.mload(item_1);
-                    }
-                    let result: String = result.try_into()?;
+                let (_, [beneficiary]) = self.pop_items::<1>(instruction.clone())?;
+                dynamic_cost = self.selfdestruct(Address::from(beneficiary))?;
 
-                    self.stack.push(result)?;
-                }
-                InstructionType::MSTORE => unsafe {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                self.charge_dynamic_gas(instruction, static_cost, dynamic_cost)?;
+                self.halted = true;
 
-                    self.memory.mstore(item_1, item_2);
-                },
-                InstructionType::SLOAD => {
-                    let item_1 = *build_initials()?.downcast::<Bytes32>().unwrap();
+                return Ok(false);
+            }
+            InstructionType::RETURN | InstructionType::REVERT => {
+                let (_, [offset, size]) = self.pop_items::<2>(instruction.clone())?;
 
-                    let result = self.storage.sload(item_1).unwrap();
-                    let result: String = result.clone().to_string();
+                let offset = offset.saturating_to_usize();
+                let size = size.saturating_to_usize();
 
-                    self.stack.push(result)?;
-                }
-                InstructionType::SSTORE => {
-                    let (item_1, item_2) =
-                        *build_initials()?.downcast::<(Bytes32, Bytes32)>().unwrap();
+                self.check_memory_expansion(offset, size)?;
 
-                    self.storage.sstore(item_1, item_2);
-                }
-                InstructionType::PUSH(size) => {
-                    if size > 32 {
-                        return Err(Box::new(VmError::IncompatibleSize(InstructionType::PUSH(
-                            size,
-                        ))));
-                    }
+                self.output = self.memory.load_bytes(offset, size);
+                self.reverted = matches!(instruction, InstructionType::REVERT);
 
-                    if size == 0 {
-                        self.stack.push("0".to_string())?;
-                        continue 'main;
-                    }
+                self.charge_dynamic_gas(instruction, static_cost, dynamic_cost)?;
+                self.halted = true;
 
-                    build_initials()?;
+                return Ok(false);
+            }
+            InstructionType::RJUMP => {
+                let relative = self.read_immediate()? as i16;
+
+                self.relative_jump(relative);
+            }
+            InstructionType::RJUMPI => {
+                let condition = *build_initials()?.downcast::<Bytes32>().unwrap();
+                let relative = self.read_immediate()? as i16;
+
+                if condition != Bytes32::from(0) {
+                    self.relative_jump(relative);
                 }
-                InstructionType::DUP(size) => {
-                    if size == 0 || size > 16 {
-                        return Err(Box::new(VmError::IncompatibleSize(InstructionType::DUP(
-                            size,
-                        ))));
-                    }
+            }
+            InstructionType::CALLF => {
+                let index = self.read_immediate()? as usize;
+                self.call_function(index)?;
+            }
+            InstructionType::RETF => {
+                let (section, offset) =
+                    self.return_stack.pop().ok_or(StackError::StackUnderflow)?;
 
-                    build_initials()?;
+                self.code_section = section;
+                self.lexer.seek(offset);
+            }
+            InstructionType::DUP(size) => {
+                if size == 0 || size > 16 {
+                    return Err(Box::new(VmError::IncompatibleSize(InstructionType::DUP(
+                        size,
+                    ))));
                 }
-                InstructionType::SWAP(size) => {
-                    if size == 0 || size > 16 {
-                        return Err(Box::new(VmError::IncompatibleSize(InstructionType::SWAP(
-                            size,
-                        ))));
-                    }
 
-                    build_initials()?;
+                build_initials()?;
+            }
+            InstructionType::SWAP(size) => {
+                if size == 0 || size > 16 {
+                    return Err(Box::new(VmError::IncompatibleSize(InstructionType::SWAP(
+                        size,
+                    ))));
                 }
+
+                build_initials()?;
             }
         }
 
-        Ok(())
+        self.charge_dynamic_gas(instruction, static_cost, dynamic_cost)?;
+
+        Ok(self.lexer.ch != '\0')
     }
 
-    fn pop_first_item(
+    fn charge_dynamic_gas(
         &mut self,
         instruction: InstructionType,
-    ) -> Result<(usize, Bytes32), Box<dyn Error>> {
-        if self.stack.is_empty() {
-            return Err(Box::new(VmError::ShallowStack(Box::leak(Box::new(
-                instruction,
-            )))));
+        static_cost: u64,
+        dynamic_cost: u64,
+    ) -> Result<(), Box<dyn Error>> {
+        let memory_expansion_cost = self.memory.take_expansion_cost();
+
+        self.gas.charge(dynamic_cost + memory_expansion_cost)?;
+
+        let info = GasInfo {
+            instruction,
+            static_cost,
+            dynamic_cost,
+            memory_expansion_cost,
+            remaining: self.gas.remaining(),
+        };
+
+        if self.profile.is_some() {
+            self.history.save_gas_usage(info.clone());
         }
 
-        let (index, item) = &self.stack.pop()?;
+        if self.verbose {
+            self.history.save_on_event(Component::Gas(info))?;
+        }
 
-        Ok((*index, Bytes32::from_str(item.as_str())?))
+        Ok(())
     }
 
-    fn pop_first_two_items(
+    fn push_item(
         &mut self,
-        instruction: InstructionType,
-    ) -> Result<([usize; 2], [Bytes32; 2]), Box<dyn Error>> {
-        if self.stack.length < 2 {
-            return Err(Box::new(VmError::ShallowStack(Box::leak(Box::new(
-                instruction,
-            )))));
+        instruction: &InstructionType,
+        item: Bytes32,
+    ) -> Result<(), Box<dyn Error>> {
+        let index = self.stack.push(item.parse_and_trim()?)?;
+
+        if self.verbose {
+            self.history
+                .save_on_event(Component::build_stack_with_one_item(
+                    instruction.clone(),
+                    item,
+                    index as u16,
+                ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Pops memory offset, source offset and size, then copies the zero-padded
+    /// source slice into memory. Returns the copy cost of the operation.
+    fn copy_to_memory(
+        &mut self,
+        instruction: &InstructionType,
+        source: &[u8],
+    ) -> Result<u64, Box<dyn Error>> {
+        let (_, [memory_offset, offset, size]) = self.pop_items::<3>(instruction.clone())?;
+
+        self.store_padded(
+            source,
+            memory_offset.saturating_to_usize(),
+            offset.saturating_to_usize(),
+            size.saturating_to_usize(),
+        )
+    }
+
+    fn store_padded(
+        &mut self,
+        source: &[u8],
+        memory_offset: usize,
+        offset: usize,
+        size: usize,
+    ) -> Result<u64, Box<dyn Error>> {
+        self.check_memory_expansion(memory_offset, size)?;
+
+        let data = read_padded(source, offset, size);
+        self.memory.store_bytes(memory_offset, &data);
+
+        if self.verbose && size > 0 {
+            self.history
+                .save_memory_location(Bytes32::from(memory_offset));
+        }
+
+        Ok(gas::copy_cost(size as u64))
+    }
+
+    /// Executes a message call and returns its dynamic cost, which covers the
+    /// account access surcharges and the gas the callee actually consumed.
+    fn call(&mut self, instruction: &InstructionType) -> Result<u64, Box<dyn Error>> {
+        let (_, [requested_gas, address]) = self.pop_items::<2>(instruction.clone())?;
+        let value = match instruction {
+            InstructionType::CALL | InstructionType::CALLCODE => {
+                let (_, [value]) = self.pop_items::<1>(instruction.clone())?;
+                value.saturating_to_u128()
+            }
+            _ => 0,
+        };
+        let (_, [args_offset, args_size, ret_offset, ret_size]) =
+            self.pop_items::<4>(instruction.clone())?;
+
+        let address = Address::from(address);
+
+        if self.is_static && value > 0 && matches!(instruction, InstructionType::CALL) {
+            return Err(Box::new(VmError::WriteProtection(instruction.clone())));
+        }
+
+        let args_offset = args_offset.saturating_to_usize();
+        let args_size = args_size.saturating_to_usize();
+        let ret_offset = ret_offset.saturating_to_usize();
+        let ret_size = ret_size.saturating_to_usize();
+
+        self.check_memory_expansion(args_offset, args_size)?;
+        self.check_memory_expansion(ret_offset, ret_size)?;
+
+        let args = self.memory.load_bytes(args_offset, args_size);
+        self.memory.load_bytes(ret_offset, ret_size);
+
+        let mut cost = self.account_access_cost(address);
+
+        if value > 0 {
+            cost += gas::CALL_VALUE_GAS;
+        }
+
+        // NOTE: before Spurious Dragon calling a missing account was charged regardless of the value (EIP-161)
+        if matches!(instruction, InstructionType::CALL) {
+            let new_account = if self.spec.is_enabled_in(SpecId::SpuriousDragon) {
+                value > 0 && self.state.is_empty(&address)
+            } else {
+                !self.state.exists(&address)
+            };
+
+            if new_account {
+                cost += gas::NEW_ACCOUNT_GAS;
+            }
+        }
+
+        // NOTE: memory expansion is charged after the call, but it still limits the forwarded gas
+        let available = self
+            .gas
+            .remaining()
+            .checked_sub(cost + self.memory.expansion_cost())
+            .ok_or(GasError::OutOfGas)?;
+        let requested = u64::try_from(requested_gas.saturating_to_usize()).unwrap_or(u64::MAX);
+        let forwarded = if self.spec.is_enabled_in(SpecId::TangerineWhistle) {
+            gas::all_but_one_64th(available).min(requested)
+        } else if requested <= available {
+            requested
+        } else {
+            return Err(Box::new(GasError::OutOfGas));
+        };
+        let stipend = if value > 0 { gas::CALL_STIPEND } else { 0 };
+
+        self.return_data = vec![];
+
+        if self.depth >= CALL_DEPTH_LIMIT || self.state.balance(&self.tx.address) < value {
+            self.push_item(instruction, Bytes32::from(false))?;
+
+            return Ok(cost);
         }
 
-        let (index_1, item_1) = &self.stack.pop()?;
-        let (index_2, item_2) = &self.stack.pop()?;
+        self.save_storage();
+        // NOTE: the whole state is copied for every nested frame, which makes deep call
+        // chains against a large state quadratic; a journal of changes would avoid it
+        let snapshot = self.state.clone();
+
+        let mut tx = self.tx.clone();
+        match instruction {
+            InstructionType::CALL | InstructionType::STATICCALL => {
+                tx.address = address;
+                tx.caller = self.tx.address;
+                tx.value = value;
+            }
+            InstructionType::CALLCODE => {
+                tx.caller = self.tx.address;
+                tx.value = value;
+            }
+            _ => {}
+        }
+
+        if value > 0 {
+            self.state.transfer(self.tx.address, tx.address, value)?;
+        }
+
+        let code = self.state.code(&address).to_vec();
+        let is_static = self.is_static || matches!(instruction, InstructionType::STATICCALL);
+        let gas_limit = forwarded + stipend;
+
+        let result = match precompile::get(&address, self.spec) {
+            Some(precompile) => run_precompile(precompile, &args, gas_limit),
+            None => self.run_frame(&code, tx, args, gas_limit, is_static)?,
+        };
+
+        if result.reverted {
+            self.state = snapshot;
+        } else {
+            self.logs.extend(result.logs);
+            self.gas.refund(result.gas_refunded);
+        }
+
+        self.load_storage();
+
+        let copy_size = ret_size.min(result.output.len());
+        self.memory
+            .store_bytes(ret_offset, &result.output[..copy_size]);
+        self.return_data = result.output;
+
+        self.push_item(instruction, Bytes32::from(!result.reverted))?;
+
+        Ok((cost + forwarded).saturating_sub(gas_limit - result.gas_used))
+    }
+
+    /// Creates a contract by running the init code in a new frame and
+    /// returns the dynamic cost of the creation.
+    fn create(&mut self, instruction: &InstructionType) -> Result<u64, Box<dyn Error>> {
+        if self.is_static {
+            return Err(Box::new(VmError::WriteProtection(instruction.clone())));
+        }
+
+        let (_, [value, offset, size]) = self.pop_items::<3>(instruction.clone())?;
+        let salt = match instruction {
+            InstructionType::CREATE2 => {
+                let (_, [salt]) = self.pop_items::<1>(instruction.clone())?;
+                Some(salt)
+            }
+            _ => None,
+        };
+
+        let value = value.saturating_to_u128();
+        let offset = offset.saturating_to_usize();
+        let size = size.saturating_to_usize();
+
+        let shanghai = self.spec.is_enabled_in(SpecId::Shanghai);
+
+        if shanghai && size > MAX_INITCODE_SIZE {
+            return Err(Box::new(VmError::InitCodeSizeLimit));
+        }
+
+        self.check_memory_expansion(offset, size)?;
+
+        let init_code = self.memory.load_bytes(offset, size);
+
+        let mut cost = if shanghai {
+            gas::initcode_cost(size as u64)
+        } else {
+            0
+        };
+        if salt.is_some() {
+            cost += gas::keccak256_cost(size as u64);
+        }
+
+        let available = self
+            .gas
+            .remaining()
+            .checked_sub(cost + self.memory.expansion_cost())
+            .ok_or(GasError::OutOfGas)?;
+        let forwarded = if self.spec.is_enabled_in(SpecId::TangerineWhistle) {
+            gas::all_but_one_64th(available)
+        } else {
+            available
+        };
+
+        self.return_data = vec![];
+
+        let nonce = self.state.nonce(&self.tx.address);
+
+        if self.depth >= CALL_DEPTH_LIMIT
+            || self.state.balance(&self.tx.address) < value
+            || nonce == u64::MAX
+        {
+            self.push_item(instruction, Bytes32::from(false))?;
+
+            return Ok(cost);
+        }
+
+        let address = match salt {
+            Some(salt) => crypto::create2_address(self.tx.address, salt, &init_code),
+            None => crypto::create_address(self.tx.address, nonce),
+        };
+
+        self.save_storage();
+        self.state.account_mut(self.tx.address).nonce += 1;
+        self.state.warm_address(address);
+
+        // NOTE: creating over an account with code, nonce or storage consumes all forwarded gas
+        if self.state.has_collision(&address) {
+            self.push_item(instruction, Bytes32::from(false))?;
+
+            return Ok(cost + forwarded);
+        }
+
+        // NOTE: copied in full like the state of a call, see `call`
+        let snapshot = self.state.clone();
+
+        if self.spec.is_enabled_in(SpecId::SpuriousDragon) {
+            self.state.account_mut(address).nonce = 1;
+        }
+        self.state.mark_created(address);
+        self.state.transfer(self.tx.address, address, value)?;
+
+        let mut tx = self.tx.clone();
+        tx.address = address;
+        tx.caller = self.tx.address;
+        tx.value = value;
+
+        let mut result = self.run_frame(&init_code, tx, vec![], forwarded, false)?;
+
+        deposit_code(&mut self.state, self.spec, address, &mut result, forwarded);
+
+        if result.reverted {
+            self.state = snapshot;
+            self.return_data = result.output;
+        } else {
+            self.logs.extend(result.logs);
+            self.gas.refund(result.gas_refunded);
+        }
+
+        self.load_storage();
+
+        let item = if result.reverted {
+            Bytes32::from(0)
+        } else {
+            Bytes32::from(address)
+        };
+        self.push_item(instruction, item)?;
+
+        Ok(cost + result.gas_used)
+    }
+
+    /// Reads the two byte immediate of an EOF instruction.
+    fn read_immediate(&mut self) -> Result<u16, Box<dyn Error>> {
+        let immediate = self.lexer.next_byte()? + &self.lexer.next_byte()?;
+
+        Ok(u16::from_str_radix(&immediate, 16)?)
+    }
+
+    /// Jumps relative to the end of the current instruction (EIP-4200).
+    fn relative_jump(&mut self, relative: i16) {
+        let target = self.lexer.byte_offset() as isize + relative as isize;
+
+        self.lexer.seek(target as usize);
+    }
+
+    /// Enters the code section after checking the return stack and the stack
+    /// height the section needs (EIP-4750, EIP-5450).
+    fn call_function(&mut self, index: usize) -> Result<(), Box<dyn Error>> {
+        let container = self.eof.as_ref().unwrap();
+        let section = container.types[index];
+        let offset = container.code_offsets[index];
+
+        let height = self.stack.length as usize + section.max_stack_height as usize
+            - section.inputs as usize;
+
+        if self.return_stack.len() >= RETURN_STACK_LIMIT || height > STACK_SIZE_LIMIT as usize {
+            return Err(Box::new(StackError::StackOverflow));
+        }
+
+        self.return_stack
+            .push((self.code_section, self.lexer.byte_offset()));
+        self.code_section = index;
+        self.lexer.seek(offset);
+
+        Ok(())
+    }
+
+    /// Warms the address and returns the cold access surcharge, which only exists since Berlin.
+    fn account_access_cost(&mut self, address: Address) -> u64 {
+        let is_cold = self.state.warm_address(address);
+
+        if self.spec.is_enabled_in(SpecId::Berlin) {
+            gas::cold_account_access_cost(is_cold)
+        } else {
+            0
+        }
+    }
+
+    /// Sends the whole balance to the beneficiary and returns the dynamic cost.
+    /// Since Cancun the account is only deleted when it was created in the same
+    /// transaction (EIP-6780), before London the deletion is refunded.
+    fn selfdestruct(&mut self, beneficiary: Address) -> Result<u64, Box<dyn Error>> {
+        let address = self.tx.address;
+        let balance = self.state.balance(&address);
+
+        let mut cost = self.account_access_cost(beneficiary);
+
+        // NOTE: before Spurious Dragon any missing beneficiary was charged (EIP-161)
+        let new_account = if self.spec.is_enabled_in(SpecId::SpuriousDragon) {
+            balance > 0 && self.state.is_empty(&beneficiary)
+        } else {
+            self.spec.is_enabled_in(SpecId::TangerineWhistle) && !self.state.exists(&beneficiary)
+        };
+
+        if new_account {
+            cost += gas::NEW_ACCOUNT_GAS;
+        }
+
+        if balance > 0 {
+            self.state.transfer(address, beneficiary, balance)?;
+        }
+
+        let destroy = !self.spec.is_enabled_in(SpecId::Cancun) || self.state.is_created(&address);
+
+        if destroy {
+            // NOTE: the balance is burnt when the beneficiary is the account itself
+            self.state.account_mut(address).balance = 0;
+
+            if self.state.mark_destructed(address) && !self.spec.is_enabled_in(SpecId::London) {
+                self.gas.refund(gas::SELFDESTRUCT_REFUND);
+            }
+        }
+
+        Ok(cost)
+    }
+
+    /// Runs the code in a child frame that shares the world state. An exceptional
+    /// halt is reported as a revert that consumed all of the given gas, while any
+    /// other error aborts the whole execution.
+    fn run_frame(
+        &mut self,
+        code: &[u8],
+        tx: TxEnv,
+        calldata: Vec<u8>,
+        gas_limit: u64,
+        is_static: bool,
+    ) -> Result<ExecutionResult, Box<dyn Error>> {
+        if code.is_empty() {
+            return Ok(ExecutionResult::default());
+        }
+
+        let bytecode = hex::encode(code);
+        let mut frame = Vm::new(&bytecode, self.verbose)?;
+        frame.gas = Gas::new(gas_limit);
+        frame.profile = self.profile;
+        frame.calldata = calldata;
+        frame.block = self.block.clone();
+        frame.tx = tx;
+        frame.depth = self.depth + 1;
+        frame.is_static = is_static;
+        frame.spec = self.spec;
+        frame.state = mem::take(&mut self.state);
+
+        // NOTE: nested frames recurse natively, so the outermost call moves them
+        // onto a thread whose stack fits the whole call depth. Errors are not `Send`,
+        // so an error other than a halt crosses the thread as its message
+        let result = if self.depth == 0 {
+            thread::scope(|scope| {
+                let handle = thread::Builder::new()
+                    .stack_size(CALL_STACK_SIZE)
+                    .spawn_scoped(scope, || {
+                        halted(frame.run()).map_err(|error| error.to_string())
+                    })?;
+
+                match handle.join() {
+                    Ok(result) => result.map_err(Box::<dyn Error>::from),
+                    Err(payload) => panic::resume_unwind(payload),
+                }
+            })
+        } else {
+            halted(frame.run())
+        };
+
+        self.state = mem::take(&mut frame.state);
+        self.history.extend(mem::take(&mut frame.history));
+
+        Ok(result?.unwrap_or(ExecutionResult {
+            gas_used: gas_limit,
+            reverted: true,
+            ..Default::default()
+        }))
+    }
+
+    /// Fails early when touching the given memory range cannot be paid for,
+    /// so that oversized offsets never allocate.
+    fn check_memory_expansion(&self, offset: usize, size: usize) -> Result<(), GasError> {
+        if size == 0 {
+            return Ok(());
+        }
+
+        let new_size = offset.saturating_add(size).max(self.memory.msize());
+        let cost = gas::memory_expansion_cost(self.memory.msize() as u64, new_size as u64);
+
+        if new_size == usize::MAX || cost > self.gas.remaining() {
+            return Err(GasError::OutOfGas);
+        }
+
+        Ok(())
+    }
+
+    fn pop_items<const N: usize>(
+        &mut self,
+        instruction: InstructionType,
+    ) -> Result<([usize; N], [Bytes32; N]), Box<dyn Error>> {
+        let (indexes, items) = self.pop_items_dynamic(instruction, N)?;
+
+        Ok((indexes.try_into().unwrap(), items.try_into().unwrap()))
+    }
+
+    fn pop_items_dynamic(
+        &mut self,
+        instruction: InstructionType,
+        count: usize,
+    ) -> Result<(Vec<usize>, Vec<Bytes32>), Box<dyn Error>> {
+        if usize::from(self.stack.length) < count {
+            return Err(Box::new(VmError::ShallowStack(Box::leak(Box::new(
+                instruction,
+            )))));
+        }
+
+        let mut indexes = Vec::with_capacity(count);
+        let mut items = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let (index, item) = self.stack.pop()?;
+
+            indexes.push(index);
+            items.push(Bytes32::from_str(&item)?);
+        }
+
+        Ok((indexes, items))
+    }
+
+    fn pop_first_item(
+        &mut self,
+        instruction: InstructionType,
+    ) -> Result<(usize, Bytes32), Box<dyn Error>> {
+        if self.stack.is_empty() {
+            return Err(Box::new(VmError::ShallowStack(Box::leak(Box::new(
+                instruction,
+            )))));
+        }
+
+        let (index, item) = &self.stack.pop()?;
+
+        Ok((*index, Bytes32::from_str(item.as_str())?))
+    }
+
+    fn pop_first_two_items(
+        &mut self,
+        instruction: InstructionType,
+    ) -> Result<([usize; 2], [Bytes32; 2]), Box<dyn Error>> {
+        if self.stack.length < 2 {
+            return Err(Box::new(VmError::ShallowStack(Box::leak(Box::new(
+                instruction,
+            )))));
+        }
+
+        let (index_1, item_1) = &self.stack.pop()?;
+        let (index_2, item_2) = &self.stack.pop()?;
+
+        Ok((
+            [*index_1, *index_2],
+            [Bytes32::from_str(item_1)?, Bytes32::from_str(item_2)?],
+        ))
+    }
+}
+
+/// Installs the code returned by the init code at the address and charges the
+/// deposit cost. A failed deposit is a revert which consumes all of the given gas.
+pub fn deposit_code(
+    state: &mut WorldState,
+    spec: SpecId,
+    address: Address,
+    result: &mut ExecutionResult,
+    gas_limit: u64,
+) {
+    if result.reverted {
+        return;
+    }
+
+    let code = mem::take(&mut result.output);
+    let deposit_cost = gas::code_deposit_cost(code.len() as u64);
+
+    let out_of_gas = deposit_cost > gas_limit - result.gas_used;
+
+    if out_of_gas && !spec.is_enabled_in(SpecId::Homestead) {
+        // NOTE: Frontier keeps the created account without code when the deposit cannot be paid
+    } else if out_of_gas
+        || (spec.is_enabled_in(SpecId::SpuriousDragon) && code.len() > MAX_CODE_SIZE)
+        || (spec.is_enabled_in(SpecId::London) && code.first() == Some(&EOF_MAGIC))
+    {
+        result.reverted = true;
+        result.gas_used = gas_limit;
+    } else {
+        result.gas_used += deposit_cost;
+        state.account_mut(address).code = code;
+    }
+}
+
+/// Returns whether the error is an exceptional halt, i.e. running out of gas, a stack
+/// overflow or underflow, an invalid opcode or a state change in a static call.
+pub fn is_exceptional_halt(error: &(dyn Error + 'static)) -> bool {
+    error.is::<GasError>()
+        || error.is::<StackError>()
+        || error.is::<InstructionError>()
+        || error.is::<VmError<'static>>()
+}
+
+/// Turns an exceptional halt of a frame into `None` and keeps any other error.
+fn halted(
+    result: Result<ExecutionResult, Box<dyn Error>>,
+) -> Result<Option<ExecutionResult>, Box<dyn Error>> {
+    match result {
+        Ok(result) => Ok(Some(result)),
+        Err(error) if is_exceptional_halt(error.as_ref()) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Runs a precompiled contract, where a failure consumes all of the given gas.
+pub fn run_precompile(precompile: Precompile, input: &[u8], gas_limit: u64) -> ExecutionResult {
+    match precompile(input, gas_limit) {
+        Ok(result) => ExecutionResult {
+            gas_used: result.gas_used,
+            output: result.output,
+            ..Default::default()
+        },
+        Err(_) => ExecutionResult {
+            gas_used: gas_limit,
+            reverted: true,
+            ..Default::default()
+        },
+    }
+}
+
+/// Reads `size` bytes from `offset`, filling the part past the end of `source` with zeroes.
+pub fn read_padded(source: &[u8], offset: usize, size: usize) -> Vec<u8> {
+    let mut data = vec![0; size];
+
+    if offset < source.len() {
+        let end = source.len().min(offset.saturating_add(size));
+        data[..end - offset].copy_from_slice(&source[offset..end]);
+    }
+
+    data
+}
+
+#[cfg(test)]
+// NOTE: the original tests compare against bool literals and borrow their bytecode
+#[allow(clippy::bool_assert_comparison, clippy::needless_borrow)]
+mod tests {
+    use crate::utils::{
+        crypto::{self, keccak256},
+        errors::EofError,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_creates_vm() -> Result<(), Box<dyn Error>> {
+        let bytecode = "0x8060";
+
+        let vm = create_vm(&bytecode)?;
+
+        assert_eq!(vm.stack.is_empty(), true);
+        assert_eq!(vm.lexer.bytecode, bytecode.strip_prefix("0x").unwrap());
+        assert_eq!(vm.memory.msize(), 0);
+        assert_eq!(vm.storage.size(), 0);
+        assert_eq!(vm.history.size(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_stop_opcode() -> Result<(), Box<dyn Error>> {
+        let bytecode = "0x600100";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "01");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_add_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 10 + 20 = 30 which is 1e in hex
+        let bytecode = "6014600a01";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1e");
+
+        // NOTE: (10 + 20) + 32 = 62 which is 3e in hex
+        let bytecode = "6020600a60140101";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "3e");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_mul_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 10 * 20 = 200 which is c8 in hex
+        let bytecode = "6014600a02";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "c8");
+
+        // NOTE: (10 * 20) * 2 = 400 which is 190 in hex
+        let bytecode = "60026014600a0202";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "190");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_sub_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 20 - 10 = 10 which is a in hex
+        let bytecode = "600a601403";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "a");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_div_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 5 / 2 = rounded as 2 which is 2 in hex
+        let bytecode = "6002600504";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_mod_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 5 % 2 = 1 which is 1 in hex
+        let bytecode = "6002600506";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_exp_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 5**2 = 25 which is 19 in hex
+        let bytecode = "600260050a";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "19");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_lt_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 5 < 10 = true which is 1 in hex
+        let bytecode = "600a600510";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_gt_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 20 > 10 = true which is 1 in hex
+        let bytecode = "600a601411";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_eq_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 10 == 10 = true which is 1 in hex
+        let bytecode = "600a600a14";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_iszero_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 10 == 0 = false which is 0 in hex
+        let bytecode = "600a15";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_and_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 1 & 1 = 1 which is 1 in hex
+        let bytecode = "6001600116";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_or_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 1 | 0 = 1 which is 1 in hex
+        let bytecode = "6000600117";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_xor_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: 1 ^ 1 = 0 which is 0 in hex
+        let bytecode = "6001600118";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_not_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: !0 = [f; 32] which is ff..ff in hex
+        let bytecode = "600019";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_byte_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: pushes 0xff to the stack and extracts its 31st byte which is ff = 255
+        let bytecode = "60ff601f1a";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "ff");
+        assert_eq!(vm.stack.length, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_keccak256_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: keccaks word "hello"
+        // result must be 1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8
+        // and found by using the command `cast keccak hello`
+
+        // hello in hex string
+        let data = "68656c6c6f";
+
+        let bytecode = format!("64{data}20");
+        let mut vm = create_vm(&bytecode)?;
+
+        vm.run()?;
+
+        assert_eq!(
+            vm.stack.peek().unwrap(),
+            "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8"
+        );
+        assert_eq!(vm.stack.length, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_pop_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: it pushes number 1 and number 2 to stack in order,
+        // then it pops an item from top
+        let bytecode = "6001600250";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "01");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_mstore_and_mload_opcodes() -> Result<(), Box<dyn Error>> {
+        // NOTE: pushes 0x20(32) and 0x80(memory location), and saves it on memory
+        let bytecode = "6020608052";
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        let data;
+        unsafe {
+            data = vm.memory.mload("80".parse::<Bytes32>()?)?;
+        }
+
+        let data: u128 = data.try_into()?;
+
+        assert_eq!(vm.stack.is_empty(), true);
+        assert_eq!(data, 32);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_sstore_and_sload_opcodes() -> Result<(), Box<dyn Error>> {
+        // NOTE: saves word "hello" in the slot of 1
+
+        // hello in hex string
+        let data = "68656c6c6f";
+        let bytecode = format!("64{data}600155");
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        let result = vm.storage.sload("01".parse::<Bytes32>()?);
+
+        assert_eq!(data, result.unwrap().parse_and_trim()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_sload_opcode_on_empty_slot() -> Result<(), Box<dyn Error>> {
+        let bytecode = "600554";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(
+            Bytes32::from_str(vm.stack.peek().unwrap())?,
+            Bytes32::from(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_against_account_storage_in_world_state() -> Result<(), Box<dyn Error>> {
+        // NOTE: loads the slot 1, adds 1 to it and stores it in the slot 2
+        let bytecode = "6001546001016002550000";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.state
+            .sstore(vm.tx.address, Bytes32::from(1), Bytes32::from(41));
+        vm.state
+            .sstore(Address::from(0xbeef), Bytes32::from(1), Bytes32::from(7));
+        vm.run()?;
+
+        assert_eq!(
+            vm.state.sload(&vm.tx.address, Bytes32::from(2)),
+            Bytes32::from(42)
+        );
+        assert_eq!(vm.storage.sload(Bytes32::from(2)), Some(&Bytes32::from(42)));
+        assert_eq!(
+            vm.state.sload(&Address::from(0xbeef), Bytes32::from(2)),
+            Bytes32::from(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_push_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: pushes 12 1 in the stack
+        let bytecode = "6b010101010101010101010101";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "010101010101010101010101");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_dup_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: duplicates 3rd stack item
+        let bytecode = "6b010101010101010101010101";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "010101010101010101010101");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_swap_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: swaps 3rd item with 1st item in the stack
+        // stack before swap [1, 2, 3]
+        // stack after swap [3, 2, 1]
+        let bytecode = "60016002600391";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "01");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_charges_static_gas() -> Result<(), Box<dyn Error>> {
+        // NOTE: PUSH1 + PUSH1 + ADD = 3 + 3 + 3
+        let bytecode = "6014600a01";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.gas.used(), 9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_charges_memory_expansion_gas() -> Result<(), Box<dyn Error>> {
+        // NOTE: PUSH1 + PUSH1 + MSTORE at 0x80 expands memory to 5 words (15 gas)
+        let bytecode = "6020608052";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.gas.used(), 3 + 3 + 3 + 15);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_charges_keccak256_and_exp_gas() -> Result<(), Box<dyn Error>> {
+        // NOTE: PUSH5 + KECCAK256 over 5 bytes = 3 + 30 + 6
+        let bytecode = "6468656c6c6f20";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.gas.used(), 39);
+
+        // NOTE: PUSH1 + PUSH1 + EXP with 1 byte exponent = 3 + 3 + 10 + 50
+        let bytecode = "600260050a";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.gas.used(), 66);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_calldataload_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: loads 32 bytes from the offset of 2, the part past the end is zero-padded
+        let bytecode = "600235";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.calldata = vec![0x11, 0x22, 0x33, 0x44];
+        vm.run()?;
+
+        assert_eq!(
+            vm.stack.peek().unwrap(),
+            "3344000000000000000000000000000000000000000000000000000000000000"
+        );
+
+        // NOTE: offsets past the end load zero
+        let bytecode = "60ff35";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.calldata = vec![0x11, 0x22, 0x33, 0x44];
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_calldatasize_opcode() -> Result<(), Box<dyn Error>> {
+        let bytecode = "36";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.calldata = vec![0; 36];
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "24");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_calldatacopy_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: copies 6 bytes from the calldata offset of 2 into the memory offset of 0
+        let bytecode = "6006600260003760005100";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.calldata = vec![0x11, 0x22, 0x33, 0x44];
+        vm.run()?;
+
+        assert_eq!(
+            vm.stack.peek().unwrap(),
+            "3344000000000000000000000000000000000000000000000000000000000000"
+        );
+        // NOTE: 3 PUSH1 (9) + CALLDATACOPY (3 + 3 copy + 3 memory) + PUSH1 (3) + MLOAD (3)
+        assert_eq!(vm.gas.used(), 24);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_codesize_opcode() -> Result<(), Box<dyn Error>> {
+        let bytecode = "60016002600338";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "7");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_codecopy_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: copies 64 bytes of the 12 byte code into memory, the rest is zero-padded
+        let bytecode = "604060006000396000510000";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.run()?;
+
+        assert_eq!(
+            vm.stack.peek().unwrap(),
+            "6040600060003960005100000000000000000000000000000000000000000000"
+        );
+        assert_eq!(vm.memory.msize(), 64);
+        // NOTE: 3 PUSH1 (9) + CODECOPY (3 + 6 copy + 6 memory) + PUSH1 (3) + MLOAD (3)
+        assert_eq!(vm.gas.used(), 30);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_tx_env_opcodes() -> Result<(), Box<dyn Error>> {
+        // NOTE: ADDRESS, ORIGIN, CALLER, CALLVALUE, GASPRICE
+        let bytecode = "303233343a";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.tx = TxEnv {
+            address: Address::from(0xc0de),
+            origin: Address::from(0xaa),
+            caller: Address::from(0xbb),
+            value: 1_000_000_000_000_000_000,
+            gas_price: 20_000_000_000,
+        };
+        vm.run()?;
+
+        let expected = ["4a817c800", "de0b6b3a7640000", "bb", "aa", "c0de"];
+        for item in expected {
+            assert_eq!(vm.stack.pop()?.1, item);
+        }
+        assert_eq!(vm.gas.used(), 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_block_env_opcodes() -> Result<(), Box<dyn Error>> {
+        // NOTE: COINBASE, TIMESTAMP, NUMBER, PREVRANDAO, GASLIMIT, CHAINID, BASEFEE
+        let bytecode = "41424344454648";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.block = BlockEnv {
+            coinbase: "95222290dd7278aa3ddd389cc1e1d165cc4bafe5".parse::<Address>()?,
+            timestamp: 1_700_000_000,
+            number: 19_000_000,
+            prevrandao: Bytes32::from(0xabcd),
+            gas_limit: 30_000_000,
+            chain_id: 11155111,
+            basefee: 7,
+            ..Default::default()
+        };
+        vm.run()?;
+
+        let expected = [
+            "7",
+            "aa36a7",
+            "1c9c380",
+            "abcd",
+            "121eac0",
+            "6553f100",
+            "95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+        ];
+        for item in expected {
+            assert_eq!(vm.stack.pop()?.1, item);
+        }
+        assert_eq!(vm.gas.used(), 14);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_blockhash_opcode() -> Result<(), Box<dyn Error>> {
+        let bytecode = "6063406064406101f440";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.block.number = 100;
+        vm.block
+            .block_hashes
+            .insert(98, "ab".repeat(32).parse::<Bytes32>()?);
+        vm.run()?;
+
+        // NOTE: the current and future blocks are not available
+        assert_eq!(vm.stack.pop()?.1, "0");
+        assert_eq!(vm.stack.pop()?.1, "0");
+        // NOTE: 99 is not given so it is derived from its number
+        assert_eq!(
+            vm.stack.pop()?.1,
+            keccak256(&Bytes32::from(99u128).0).parse_and_trim()?
+        );
+        assert_eq!(vm.gas.used(), 3 * 3 + 3 * 20);
+
+        let bytecode = "606240";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.block.number = 100;
+        vm.block
+            .block_hashes
+            .insert(98, "ab".repeat(32).parse::<Bytes32>()?);
+        vm.run()?;
+
+        assert_eq!(vm.stack.pop()?.1, "ab".repeat(32));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_log_opcodes() -> Result<(), Box<dyn Error>> {
+        // NOTE: stores 0xaabb at the end of the first word, then emits
+        // LOG0 with 2 bytes of data and LOG2 with the topics 1 and 2
+        let bytecode = "61aabb6000526002601ea0600260016002601ea2";
+
+        let mut vm = create_vm(bytecode)?;
+        let result = vm.run()?;
+
+        assert_eq!(result.logs.len(), 2);
+        assert_eq!(
+            result.logs[0],
+            Log {
+                address: vm.tx.address,
+                topics: vec![],
+                data: vec![0xaa, 0xbb],
+            }
+        );
+        assert_eq!(
+            result.logs[1].topics,
+            vec![Bytes32::from(1), Bytes32::from(2)]
+        );
+        assert_eq!(result.logs[1].data, vec![0xaa, 0xbb]);
+        assert_eq!(vm.logs, result.logs);
+        // NOTE: LOG0 = 375 + 8 * 2, LOG2 = 375 * 3 + 8 * 2
+        assert_eq!(
+            result.gas_used,
+            3 + 3 + 3 + 3 + 3 + 3 + 391 + 3 + 3 + 3 + 3 + 1141
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_gas_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: PUSH1 (3) + GAS (2) leaves 95 gas which is 5f in hex
+        let bytecode = "60015a";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.gas = Gas::new(100);
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "5f");
+        assert_eq!(vm.gas_left(), 95);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_steps_through_instructions() -> Result<(), Box<dyn Error>> {
+        let bytecode = "6001600200";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.gas = Gas::new(100);
+
+        assert!(vm.step()?);
+        assert_eq!(vm.gas_left(), 97);
+        assert!(vm.step()?);
+        assert_eq!(vm.gas_left(), 94);
+        assert!(!vm.step()?);
+        assert!(!vm.step()?);
+        assert_eq!(vm.stack.length, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_profiles_gas_when_enabled() -> Result<(), Box<dyn Error>> {
+        let bytecode = "60206080526001";
+
+        let mut vm = create_vm(bytecode)?;
+        vm.profile = Some(ProfileFormat::Text);
+        vm.run()?;
+
+        let profiles = vm.history.profile();
+
+        assert_eq!(profiles[0].opcode, "MSTORE");
+        assert_eq!(profiles[0].gas, 18);
+        assert_eq!(profiles[1].opcode, "PUSH1");
+        assert_eq!(profiles[1].count, 3);
+        assert_eq!(vm.gas.used(), profiles.iter().map(|p| p.gas).sum::<u64>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_profiles_call_gas_once() -> Result<(), Box<dyn Error>> {
+        // NOTE: calls 0xbeef, whose code pushes 7 times (21)
+        let mut vm = create_vm("6000600060006000600061beef61fffff1")?;
+        vm.profile = Some(ProfileFormat::Text);
+        vm.state.account_mut(Address::from(0xbeef)).code =
+            hex::decode("6001600160016001600160016001")?;
+        vm.run()?;
+
+        let profiles = vm.history.profile();
+        let call = profiles.iter().find(|p| p.opcode == "CALL").unwrap();
+
+        // NOTE: cold access (2600) + the gas used by the callee (21)
+        assert_eq!(call.gas, 2_621);
+        assert_eq!(
+            profiles.iter().find(|p| p.opcode == "PUSH1").unwrap().count,
+            5
+        );
+        assert_eq!(vm.gas.used(), profiles.iter().map(|p| p.gas).sum::<u64>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_internal_error_in_call_returns_error() -> Result<(), Box<dyn Error>> {
+        // NOTE: a stack underflow in the callee is a halt, so the call only fails
+        let mut vm = create_vm("6000600060006000600061beef61fffff1")?;
+        vm.state.account_mut(Address::from(0xbeef)).code = hex::decode("01")?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.pop()?.1, "0");
+
+        // NOTE: an invalid EOF container is not a halt and aborts the whole execution
+        let mut vm = create_vm("6000600060006000600061beef61fffff1")?;
+        vm.spec = SpecId::Prague;
+        vm.state.account_mut(Address::from(0xbeef)).code = hex::decode("ef0001")?;
+        let result = vm.run();
+
+        assert!(result.is_err_and(|error| error.to_string() == EofError::InvalidHeader.to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_return_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: stores 42 at memory location 0 and returns the first word
+        let bytecode = "602a60005260206000f3";
+
+        let mut vm = create_vm(bytecode)?;
+        let result = vm.run()?;
+
+        assert_eq!(result.output, Bytes32::from(42).0.to_vec());
+        assert!(!result.reverted);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_runs_revert_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: stores 1 in the slot 1, then reverts with empty data
+        let bytecode = "600160015560006000fd";
+
+        let mut vm = create_vm(bytecode)?;
+        let result = vm.run()?;
+
+        assert!(result.reverted);
+        assert!(result.output.is_empty());
+        assert!(!vm.state.exists(&vm.tx.address));
 
-        Ok((
-            [*index_1, *index_2],
-            [Bytes32::from_str(item_1)?, Bytes32::from_str(item_2)?],
-        ))
+        Ok(())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn it_creates_vm() -> Result<(), Box<dyn Error>> {
-        let bytecode = "0x8060";
+    fn it_runs_call_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: calls 0xbeef which returns 42, then pushes RETURNDATASIZE
+        let bytecode = "6020600060006000600061beef5af13d";
 
-        let vm = create_vm(&bytecode)?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(Address::from(0xbeef)).code = hex::decode("602a60005260206000f3")?;
+        vm.run()?;
 
-        assert_eq!(vm.stack.is_empty(), true);
-        assert_eq!(vm.lexer.bytecode, bytecode.strip_prefix("0x").unwrap());
-        assert_eq!(vm.memory.msize(), 0);
-        assert_eq!(vm.storage.size(), 0);
-        assert_eq!(vm.history.size(), 0);
+        assert_eq!(vm.stack.pop()?.1, "20");
+        assert_eq!(vm.stack.pop()?.1, "1");
+        assert_eq!(vm.return_data, Bytes32::from(42).0.to_vec());
+        assert_eq!(vm.memory.load_bytes(0, 32), Bytes32::from(42).0.to_vec());
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_stop_opcode() -> Result<(), Box<dyn Error>> {
-        let bytecode = "0x600100";
+    fn it_runs_call_opcode_with_value() -> Result<(), Box<dyn Error>> {
+        // NOTE: sends 5 wei to 0xbeef which stores 1 in its slot 0
+        let bytecode = "6000600060006000600561beef5af1";
+        let callee = Address::from(0xbeef);
 
-        let mut vm = create_vm(&bytecode)?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(vm.tx.address).balance = 10;
+        vm.state.account_mut(callee).code = hex::decode("6001600055")?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "01");
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+        assert_eq!(vm.state.balance(&vm.tx.address), 5);
+        assert_eq!(vm.state.balance(&callee), 5);
+        assert_eq!(vm.state.sload(&callee, Bytes32::from(0)), Bytes32::from(1));
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_add_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 10 + 20 = 30 which is 1e in hex
-        let bytecode = "6014600a01";
+    fn it_rolls_back_state_when_callee_reverts() -> Result<(), Box<dyn Error>> {
+        let bytecode = "6000600060006000600561beef5af1";
+        let callee = Address::from(0xbeef);
 
-        let mut vm = create_vm(&bytecode)?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(vm.tx.address).balance = 10;
+        vm.state.account_mut(callee).code = hex::decode("600160005560006000fd")?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "1e");
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+        assert_eq!(vm.state.balance(&vm.tx.address), 10);
+        assert_eq!(vm.state.sload(&callee, Bytes32::from(0)), Bytes32::from(0));
 
-        // NOTE: (10 + 20) + 32 = 62 which is 3e in hex
-        let bytecode = "6020600a60140101";
+        Ok(())
+    }
 
-        let mut vm = create_vm(&bytecode)?;
+    #[test]
+    fn it_runs_delegatecall_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: the callee code stores 1 in the slot 0 of the calling account
+        let bytecode = "600060006000600061beef5af4";
+        let callee = Address::from(0xbeef);
+
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(callee).code = hex::decode("6001600055")?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "3e");
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+        assert_eq!(
+            vm.state.sload(&vm.tx.address, Bytes32::from(0)),
+            Bytes32::from(1)
+        );
+        assert_eq!(vm.state.sload(&callee, Bytes32::from(0)), Bytes32::from(0));
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_mul_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 10 * 20 = 200 which is c8 in hex
-        let bytecode = "6014600a02";
+    fn it_runs_staticcall_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: the callee tries to store in a static context, so the call fails
+        let bytecode = "600060006000600061beef5afa";
+        let callee = Address::from(0xbeef);
 
-        let mut vm = create_vm(&bytecode)?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(callee).code = hex::decode("6001600055")?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "c8");
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+        assert_eq!(vm.state.sload(&callee, Bytes32::from(0)), Bytes32::from(0));
 
-        // NOTE: (10 * 20) * 2 = 400 which is 190 in hex
-        let bytecode = "60026014600a0202";
+        Ok(())
+    }
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+    #[test]
+    fn it_charges_cold_account_access_on_call() -> Result<(), Box<dyn Error>> {
+        // NOTE: 6 pushes (18) + GAS (2) + CALL (100) + cold access (2500)
+        let bytecode = "6000600060006000600061beef5af1";
 
-        assert_eq!(vm.stack.peek().unwrap(), "190");
+        let mut vm = create_vm(bytecode)?;
+        let result = vm.run()?;
+
+        assert_eq!(result.gas_used, 2_620);
+        assert!(vm.state.is_warm(&Address::from(0xbeef)));
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_sub_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 20 - 10 = 10 which is a in hex
-        let bytecode = "600a601403";
+    fn it_reaches_call_depth_limit() -> Result<(), Box<dyn Error>> {
+        // NOTE: calls itself with all but 0x1000 gas and logs once the call returns,
+        // there is no 63/64 rule before Tangerine Whistle to stop the recursion early
+        let mut vm = create_vm("60006000600060006000306110005a03f160006000a0")?;
+        vm.spec = SpecId::Frontier;
+        vm.state.account_mut(vm.tx.address).code = vm.code.clone();
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+        let result = vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "a");
+        assert!(!result.reverted);
+        assert_eq!(result.logs.len(), CALL_DEPTH_LIMIT + 1);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_div_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 5 / 2 = rounded as 2 which is 2 in hex
-        let bytecode = "6002600504";
+    fn it_charges_call_costs_per_spec() -> Result<(), Box<dyn Error>> {
+        // NOTE: 6 pushes (18) + GAS (2) + CALL (700) without cold access before Berlin
+        let mut vm = create_vm("6000600060006000600061beef5af1")?;
+        vm.spec = SpecId::Istanbul;
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+        assert_eq!(vm.run()?.gas_used, 720);
 
-        assert_eq!(vm.stack.peek().unwrap(), "2");
+        // NOTE: 7 pushes (21) + CALL (40) + new account (25000) before Spurious Dragon
+        let mut vm = create_vm("6000600060006000600061beef6000f1")?;
+        vm.spec = SpecId::Homestead;
+
+        assert_eq!(vm.run()?.gas_used, 25_061);
+
+        // NOTE: without the 63/64 rule the requested gas must be available
+        let mut vm = create_vm("6000600060006000600061beef5af1")?;
+        vm.spec = SpecId::Homestead;
+
+        assert!(vm.run().is_err());
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_mod_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 5 % 2 = 1 which is 1 in hex
-        let bytecode = "6002600506";
+    fn it_charges_sload_per_spec() -> Result<(), Box<dyn Error>> {
+        let mut vm = create_vm("600054")?;
+        vm.spec = SpecId::Frontier;
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+        assert_eq!(vm.run()?.gas_used, 53);
 
-        assert_eq!(vm.stack.peek().unwrap(), "1");
+        // NOTE: the second load of the slot is warm (EIP-2929)
+        let mut vm = create_vm("600154600154")?;
+
+        assert_eq!(vm.run()?.gas_used, 2_206);
+        assert!(vm.state.is_warm_slot(&vm.tx.address, Bytes32::from(1)));
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_exp_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 5**2 = 25 which is 19 in hex
-        let bytecode = "600260050a";
+    fn it_charges_sstore_on_dirty_slot() -> Result<(), Box<dyn Error>> {
+        // NOTE: sets slot 0 to 1 (cold, 22100) and then to 2 (dirty, 100)
+        let mut vm = create_vm("60016000556002600055")?;
 
-        let mut vm = create_vm(&bytecode)?;
+        assert_eq!(vm.run()?.gas_used, 22_212);
+
+        // NOTE: before Istanbul both writes are priced on the current value only
+        let mut vm = create_vm("60016000556002600055")?;
+        vm.spec = SpecId::Petersburg;
+
+        assert_eq!(vm.run()?.gas_used, 25_012);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_refunds_cleared_slots_per_spec() -> Result<(), Box<dyn Error>> {
+        let run = |spec: SpecId| -> Result<(ExecutionResult, i64), Box<dyn Error>> {
+            // NOTE: clears the slot 0 which holds 1
+            let mut vm = create_vm("6000600055")?;
+            vm.spec = spec;
+            vm.state
+                .sstore(vm.tx.address, Bytes32::from(0), Bytes32::from(1));
+
+            let result = vm.run()?;
+
+            Ok((result, vm.gas.refunded()))
+        };
+
+        // NOTE: the refund is capped to the half of 5006 before London
+        let (result, refunded) = run(SpecId::Berlin)?;
+        assert_eq!(result.gas_used, 5_006);
+        assert_eq!(refunded, 15_000);
+        assert_eq!(result.gas_refunded, 2_503);
+
+        // NOTE: and to the fifth since London, which lowers the refund as well (EIP-3529)
+        let (result, refunded) = run(SpecId::London)?;
+        assert_eq!(refunded, 4_800);
+        assert_eq!(result.gas_refunded, 1_001);
+
+        // NOTE: setting and clearing a slot in one go refunds all but the warm reads
+        let mut vm = create_vm("60016000556000600055")?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "19");
+        assert_eq!(vm.gas.used(), 22_212);
+        assert_eq!(vm.gas.refunded(), 19_900);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_lt_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 5 < 10 = true which is 1 in hex
-        let bytecode = "600a600510";
+    fn test_sstore_within_stipend_returns_gas_error() -> Result<(), Box<dyn Error>> {
+        // NOTE: the no-op write costs 2200 but the call stipend of 2300 is left
+        let mut vm = create_vm("6000600055")?;
+        vm.gas = Gas::new(2_306);
 
-        let mut vm = create_vm(&bytecode)?;
+        assert!(matches!(
+            vm.run(),
+            Err(e) if e.downcast_ref::<GasError>() == Some(&GasError::OutOfGas)
+        ));
+
+        // NOTE: there is no sentry before Istanbul
+        let mut vm = create_vm("6000600055")?;
+        vm.gas = Gas::new(5_006);
+        vm.spec = SpecId::Petersburg;
+
+        assert_eq!(vm.run()?.gas_used, 5_006);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inactive_opcode_returns_vm_error() -> Result<(), Box<dyn Error>> {
+        let mut vm = create_vm("5f")?;
+        vm.spec = SpecId::Merge;
+        let result = vm.run();
+
+        assert!(matches!(
+            result,
+            Err(e) if matches!(
+                e.downcast_ref::<VmError>(),
+                Some(VmError::NotActivated(InstructionType::PUSH(0), SpecId::Merge))
+            )
+        ));
+
+        let mut vm = create_vm("5f")?;
+        vm.spec = SpecId::Shanghai;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "1");
+        assert_eq!(vm.stack.peek().unwrap(), "0");
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_gt_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 20 > 10 = true which is 1 in hex
-        let bytecode = "600a601411";
+    fn it_runs_create_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: the init code returns the runtime code which returns 42
+        let runtime_code = "602a60005260206000f3";
+        let init_code = format!("69{runtime_code}600052600a6016f3");
+        let bytecode = create_bytecode(&init_code, "f0");
 
         let mut vm = create_vm(&bytecode)?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "1");
+        let address = crypto::create_address(vm.tx.address, 0);
+
+        assert_eq!(
+            vm.stack.peek().unwrap(),
+            &Bytes32::from(address).parse_and_trim()?
+        );
+        assert_eq!(vm.state.code(&address), hex::decode(runtime_code)?);
+        assert_eq!(vm.state.nonce(&address), 1);
+        assert_eq!(vm.state.nonce(&vm.tx.address), 1);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_eq_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 10 == 10 = true which is 1 in hex
-        let bytecode = "600a600a14";
+    fn it_runs_create2_opcode() -> Result<(), Box<dyn Error>> {
+        let init_code = "69602a60005260206000f3600052600a6016f3";
+        // NOTE: deploys twice with the same salt, the second one collides
+        let bytecode = create_bytecode(init_code, "f5").repeat(2);
 
         let mut vm = create_vm(&bytecode)?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "1");
+        let address =
+            crypto::create2_address(vm.tx.address, Bytes32::from(1), &hex::decode(init_code)?);
+
+        assert_eq!(vm.stack.pop()?.1, "0");
+        assert_eq!(vm.stack.pop()?.1, Bytes32::from(address).parse_and_trim()?);
+        assert_eq!(
+            vm.state.code(&address),
+            hex::decode("602a60005260206000f3")?
+        );
+        assert_eq!(vm.state.nonce(&vm.tx.address), 2);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_iszero_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 10 == 0 = false which is 0 in hex
-        let bytecode = "600a15";
+    fn it_fails_create_when_init_code_reverts() -> Result<(), Box<dyn Error>> {
+        // NOTE: the init code reverts with a 32 bytes word
+        let bytecode = create_bytecode("600160005260206000fd", "f0");
 
         let mut vm = create_vm(&bytecode)?;
         vm.run()?;
 
+        let address = crypto::create_address(vm.tx.address, 0);
+
         assert_eq!(vm.stack.peek().unwrap(), "0");
+        assert!(!vm.state.exists(&address));
+        assert_eq!(vm.return_data, Bytes32::from(1).0.to_vec());
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_and_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 1 & 1 = 1 which is 1 in hex
-        let bytecode = "6001600116";
+    fn it_rejects_invalid_deployed_code() -> Result<(), Box<dyn Error>> {
+        // NOTE: returns 0x6001 bytes which exceeds the code size limit
+        let bytecode = create_bytecode("6160016000f3", "f0");
 
         let mut vm = create_vm(&bytecode)?;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "1");
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+
+        // NOTE: returns the single byte 0xef
+        let bytecode = create_bytecode("60ef6000526001601ff3", "f0");
+
+        let mut vm = create_vm(&bytecode)?;
+        vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+        assert_eq!(vm.state.nonce(&vm.tx.address), 1);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_or_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 1 | 0 = 1 which is 1 in hex
-        let bytecode = "6000600117";
+    fn it_runs_balance_and_selfbalance_opcodes() -> Result<(), Box<dyn Error>> {
+        // NOTE: reads the balance of 0xbeef twice, then the own balance
+        let bytecode = "61beef3161beef3147";
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(Address::from(0xbeef)).balance = 7;
+        vm.state.account_mut(vm.tx.address).balance = 10;
+        let result = vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "1");
+        assert_eq!(vm.stack.pop()?.1, "a");
+        assert_eq!(vm.stack.pop()?.1, "7");
+        assert_eq!(vm.stack.pop()?.1, "7");
+        // NOTE: 2 pushes (6) + cold BALANCE (2600) + warm BALANCE (100) + SELFBALANCE (5)
+        assert_eq!(result.gas_used, 2_711);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_xor_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: 1 ^ 1 = 0 which is 0 in hex
-        let bytecode = "6001600118";
+    fn it_runs_extcodesize_and_extcodecopy_opcodes() -> Result<(), Box<dyn Error>> {
+        // NOTE: copies the 3 bytes code of 0xbeef to memory location 0
+        let bytecode = "61beef3b60036000600061beef3c";
 
-        let mut vm = create_vm(&bytecode)?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(Address::from(0xbeef)).code = vec![0x60, 0x01, 0x00];
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "0");
+        assert_eq!(vm.stack.peek().unwrap(), "3");
+        assert_eq!(vm.memory.load_bytes(0, 3), vec![0x60, 0x01, 0x00]);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_not_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: !0 = [f; 32] which is ff..ff in hex
-        let bytecode = "600019";
+    fn it_runs_extcodehash_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: hashes a non-existent, an empty and a contract account in order
+        let bytecode = "61dead3f61cafe3f61beef3f";
 
-        let mut vm = create_vm(&bytecode)?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(Address::from(0xcafe));
+        vm.state.account_mut(Address::from(0xbeef)).code = vec![0x00];
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "1");
+        assert_eq!(vm.stack.pop()?.1, keccak256(&[0x00]).parse_and_trim()?);
+        assert_eq!(vm.stack.pop()?.1, "0");
+        assert_eq!(vm.stack.pop()?.1, "0");
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_byte_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: pushes 0xff to the stack and extracts its 31st byte which is ff = 255
-        let bytecode = "60ff601f1a";
+    fn it_runs_selfdestruct_opcode() -> Result<(), Box<dyn Error>> {
+        // NOTE: sends the balance to 0xbeef, the code survives since Cancun
+        let bytecode = "61beefff";
+        let beneficiary = Address::from(0xbeef);
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(vm.tx.address).balance = 10;
+        vm.state.account_mut(vm.tx.address).code = hex::decode(bytecode)?;
+        let result = vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "ff");
-        assert_eq!(vm.stack.length, 1);
+        assert_eq!(vm.state.balance(&beneficiary), 10);
+        assert_eq!(vm.state.balance(&vm.tx.address), 0);
+        assert_eq!(vm.state.code(&vm.tx.address), hex::decode(bytecode)?);
+        // NOTE: PUSH2 (3) + SELFDESTRUCT (5000) + cold access (2500) + new account (25000)
+        assert_eq!(result.gas_used, 32_503);
+        assert_eq!(result.gas_refunded, 0);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_keccak256_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: keccaks word "hello"
-        // result must be 1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8
-        // and found by using the command `cast keccak hello`
+    fn it_deletes_account_created_in_the_same_transaction() -> Result<(), Box<dyn Error>> {
+        // NOTE: the init code self-destructs to 0xbeef
+        let bytecode = create_bytecode("61beefff", "f0");
 
-        // hello in hex string
-        let data = "68656c6c6f";
-
-        let bytecode = format!("64{data}20");
         let mut vm = create_vm(&bytecode)?;
-
         vm.run()?;
 
+        let address = crypto::create_address(vm.tx.address, 0);
+
         assert_eq!(
             vm.stack.peek().unwrap(),
-            "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8"
+            &Bytes32::from(address).parse_and_trim()?
         );
-        assert_eq!(vm.stack.length, 1);
+        assert!(!vm.state.exists(&address));
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_pop_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: it pushes number 1 and number 2 to stack in order,
-        // then it pops an item from top
-        let bytecode = "6001600250";
+    fn it_runs_selfdestruct_opcode_before_cancun() -> Result<(), Box<dyn Error>> {
+        let bytecode = "61beefff";
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+        let mut vm = create_vm(bytecode)?;
+        vm.spec = SpecId::Berlin;
+        vm.state.account_mut(vm.tx.address).code = hex::decode(bytecode)?;
+        let result = vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "01");
+        assert!(!vm.state.exists(&vm.tx.address));
+        // NOTE: the refund is capped to the half of the used gas
+        assert_eq!(result.gas_used, 7_503);
+        assert_eq!(result.gas_refunded, 3_751);
+
+        let mut vm = create_vm(bytecode)?;
+        vm.spec = SpecId::London;
+        vm.state.account_mut(vm.tx.address).code = hex::decode(bytecode)?;
+        let result = vm.run()?;
+
+        assert!(!vm.state.exists(&vm.tx.address));
+        assert_eq!(result.gas_refunded, 0);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_mstore_and_mload_opcodes() -> Result<(), Box<dyn Error>> {
-        // NOTE: pushes 0x20(32) and 0x80(memory location), and saves it on memory
-        let bytecode = "6020608052";
+    fn test_selfdestruct_in_static_context_fails_the_call() -> Result<(), Box<dyn Error>> {
+        let bytecode = "600060006000600061beef5afa";
+        let callee = Address::from(0xbeef);
 
-        let mut vm = create_vm(&bytecode)?;
+        let mut vm = create_vm(bytecode)?;
+        vm.state.account_mut(callee).code = hex::decode("61cafeff")?;
+        vm.state.account_mut(callee).balance = 1;
         vm.run()?;
 
-        let data;
-        unsafe {
-            data = vm.memory.mload("80".parse::<Bytes32>()?);
-        }
+        assert_eq!(vm.stack.peek().unwrap(), "0");
+        assert_eq!(vm.state.balance(&callee), 1);
 
-        let data: u128 = data.try_into()?;
+        Ok(())
+    }
 
-        assert_eq!(vm.stack.is_empty(), true);
-        assert_eq!(data, 32);
+    #[test]
+    fn it_calls_precompiles() -> Result<(), Box<dyn Error>> {
+        // NOTE: stores 42 and copies it to memory location 32 with the identity precompile
+        let bytecode = "602a600052602060206020600060045afa";
+
+        let mut vm = create_vm(bytecode)?;
+        let result = vm.run()?;
+
+        assert_eq!(vm.stack.peek().unwrap(), "1");
+        assert_eq!(vm.memory.load_bytes(32, 32), Bytes32::from(42).0.to_vec());
+        assert_eq!(vm.return_data, Bytes32::from(42).0.to_vec());
+        // NOTE: 7 pushes (21) + MSTORE (6) + GAS (2) + warm STATICCALL (100)
+        // + memory expansion (3) + identity (18)
+        assert_eq!(result.gas_used, 150);
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_sstore_and_sload_opcodes() -> Result<(), Box<dyn Error>> {
-        // NOTE: saves word "hello" in the slot of 1
+    fn it_runs_eof_functions() -> Result<(), Box<dyn Error>> {
+        // NOTE: section 0 calls section 1 with 3 and returns the result,
+        // section 1 multiplies its input by 2
+        let bytecode = "ef0001010008020002000d000404000000\
+                        0000000201010002\
+                        6003e3000160005260206000f3\
+                        600202e4";
 
-        // hello in hex string
-        let data = "68656c6c6f";
-        let bytecode = format!("64{data}600155");
+        let mut vm = create_vm(bytecode)?;
+        vm.spec = SpecId::Prague;
+        let result = vm.run()?;
 
-        let mut vm = create_vm(&bytecode)?;
-        vm.run()?;
+        assert_eq!(result.output, Bytes32::from(6).0.to_vec());
+        assert!(vm.eof.is_some());
+        assert!(vm.return_stack.is_empty());
 
-        let result = vm.storage.sload("01".parse::<Bytes32>()?);
+        Ok(())
+    }
 
-        assert_eq!(data, result.unwrap().parse_and_trim()?);
+    #[test]
+    fn it_runs_eof_relative_jumps() -> Result<(), Box<dyn Error>> {
+        // NOTE: PUSH1 condition, RJUMPI +3, PUSH1 1, STOP, PUSH1 2, STOP
+        for (condition, expected) in [("00", "01"), ("01", "02")] {
+            let bytecode = format!(
+                "ef0001010004020001000b04000000\
+                 00000001\
+                 60{condition}e10003600100600200"
+            );
+
+            let mut vm = create_vm(&bytecode)?;
+            vm.spec = SpecId::Prague;
+            vm.run()?;
+
+            assert_eq!(vm.stack.peek().unwrap(), expected);
+        }
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_push_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: pushes 12 1 in the stack
-        let bytecode = "6b010101010101010101010101";
+    fn test_eof_code_outside_container_returns_error() -> Result<(), Box<dyn Error>> {
+        // NOTE: RJUMP is only valid inside a container
+        let mut vm = create_vm("e00000")?;
+        vm.spec = SpecId::Prague;
+
+        assert!(vm.run().is_err());
 
+        // NOTE: before Prague a container is legacy code starting with an invalid opcode
+        let bytecode = "ef00010100040200010001040000000000000000";
         let mut vm = create_vm(bytecode)?;
+        vm.spec = SpecId::Cancun;
+
+        assert!(vm.run().is_err());
+
+        let mut vm = create_vm(bytecode)?;
+        vm.spec = SpecId::Prague;
         vm.run()?;
 
-        assert_eq!(vm.stack.peek().unwrap(), "010101010101010101010101");
+        // NOTE: invalid containers are rejected before execution
+        let bytecode = "ef00010100040200010001040000000000000000ff";
+        let mut vm = create_vm(bytecode)?;
+        vm.spec = SpecId::Prague;
+        let result = vm.run();
+
+        assert!(matches!(
+            result,
+            Err(e) if matches!(e.downcast_ref::<EofError>(), Some(EofError::InvalidContainerSize))
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_dup_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: duplicates 3rd stack item
-        let bytecode = "6b010101010101010101010101";
+    fn test_returndatacopy_out_of_bounds_returns_vm_error() -> Result<(), Box<dyn Error>> {
+        let bytecode = "6001600060003e";
 
         let mut vm = create_vm(bytecode)?;
-        vm.run()?;
+        let result = vm.run();
 
-        assert_eq!(vm.stack.peek().unwrap(), "010101010101010101010101");
+        assert!(matches!(
+            result,
+            Err(e) if matches!(e.downcast_ref::<VmError>(), Some(VmError::ReturnDataOutOfBounds))
+        ));
 
         Ok(())
     }
 
     #[test]
-    fn it_runs_swap_opcode() -> Result<(), Box<dyn Error>> {
-        // NOTE: swaps 3rd item with 1st item in the stack
-        // stack before swap [1, 2, 3]
-        // stack after swap [3, 2, 1]
-        let bytecode = "60016002600391";
+    fn test_run_out_of_gas_returns_gas_error() -> Result<(), Box<dyn Error>> {
+        let bytecode = "6020608052";
 
         let mut vm = create_vm(bytecode)?;
-        vm.run()?;
+        vm.gas = Gas::new(10);
 
-        assert_eq!(vm.stack.peek().unwrap(), "01");
+        let result = vm.run();
+
+        assert!(matches!(
+            result,
+            Err(e) if e.downcast_ref::<GasError>() == Some(&GasError::OutOfGas)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_huge_memory_offset_returns_gas_error() -> Result<(), Box<dyn Error>> {
+        // NOTE: MSTORE at 0xff00000000 and MLOAD at the max offset
+        let bytecodes = [
+            "600164ff0000000052".to_string(),
+            format!("7f{}51", "ff".repeat(32)),
+        ];
+
+        for bytecode in bytecodes {
+            let mut vm = create_vm(&bytecode)?;
+            let result = vm.run();
+
+            assert!(matches!(
+                result,
+                Err(e) if e.downcast_ref::<GasError>() == Some(&GasError::OutOfGas)
+            ));
+            assert_eq!(vm.memory.msize(), 0);
+        }
 
         Ok(())
     }
 
     // NOTE: helper function
-    fn create_vm(bytecode: &str) -> Result<Vm, Box<dyn Error>> {
-        Ok(Vm::new(bytecode, false)?)
+    fn create_vm(bytecode: &str) -> Result<Vm<'_>, Box<dyn Error>> {
+        Vm::new(bytecode, false)
+    }
+
+    // NOTE: helper function, stores the init code at the end of the first word
+    // and creates a contract from it with no value, CREATE2 uses the salt 1
+    fn create_bytecode(init_code: &str, opcode: &str) -> String {
+        let size = init_code.len() / 2;
+        let salt = if opcode == "f5" { "6001" } else { "" };
+
+        format!(
+            "{:02x}{init_code}600052{salt}60{:02x}60{:02x}6000{opcode}",
+            0x5f + size,
+            size,
+            32 - size
+        )
     }
 }
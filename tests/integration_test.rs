@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)]
+
 use std::error::Error;
 
 use cubipods::utils::bytes32::Bytes32;